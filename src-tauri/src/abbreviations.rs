@@ -0,0 +1,151 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clipboard::{log_line, now_ms};
+
+// 只需要保留不超过最长缩写的字符，超出的部分丢弃
+const BUFFER_LIMIT: usize = 64;
+
+/// 在任意程序里输入 trigger 后，删掉缩写并粘贴 target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Abbreviation {
+    /// 触发替换的缩写，例如 ;sig，区分大小写
+    pub trigger: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub target: AbbreviationTarget,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 缩写替换成的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AbbreviationTarget {
+    /// 直接写在设置里的文本，支持片段的占位符（{{input}} 除外）
+    Text {
+        text: String,
+    },
+    Snippet {
+        id: i64,
+    },
+    /// 历史记录，按默认粘贴模式写回
+    Item {
+        id: i64,
+    },
+}
+
+type Handler = Arc<dyn Fn(Abbreviation) + Send + Sync + 'static>;
+
+static BUFFER: Mutex<String> = Mutex::new(String::new());
+// 启用的缩写，设置加载或保存时刷新，按键时不用再读取设置
+static TRIGGERS: Mutex<Vec<Abbreviation>> = Mutex::new(Vec::new());
+static HANDLER: OnceLock<Handler> = OnceLock::new();
+// 自己模拟输入期间不匹配缩写
+static SUSPEND_UNTIL: AtomicI64 = AtomicI64::new(0);
+
+/// 设置命中缩写时的回调；回调在键盘钩子线程上同步执行，必须尽快返回
+pub fn set_handler<F>(handler: F)
+where
+    F: Fn(Abbreviation) + Send + Sync + 'static,
+{
+    let _ = HANDLER.set(Arc::new(handler));
+}
+
+/// 设置加载或保存后调用，更新按键时匹配的缩写；超过缓冲区长度的缩写永远不会命中，直接跳过
+pub fn reload(abbreviations: &[Abbreviation]) {
+    let triggers = abbreviations
+        .iter()
+        .filter(|abbreviation| abbreviation.enabled && !abbreviation.trigger.is_empty())
+        .filter(|abbreviation| {
+            let valid = !too_long(abbreviation);
+            if !valid {
+                log_line(&format!(
+                    "abbreviation: skip {}, too long",
+                    abbreviation.trigger
+                ));
+            }
+            valid
+        })
+        .cloned()
+        .collect();
+    *TRIGGERS.lock().unwrap() = triggers;
+}
+
+/// 保存设置前检查缩写长度
+pub fn validate(abbreviations: &[Abbreviation]) -> Result<(), String> {
+    match abbreviations
+        .iter()
+        .find(|abbreviation| too_long(abbreviation))
+    {
+        Some(abbreviation) => Err(format!(
+            "缩写 {} 超过 {BUFFER_LIMIT} 个字符",
+            abbreviation.trigger
+        )),
+        None => Ok(()),
+    }
+}
+
+fn too_long(abbreviation: &Abbreviation) -> bool {
+    abbreviation.trigger.chars().count() > BUFFER_LIMIT
+}
+
+/// 在 duration 内忽略键盘输入，逐字输入等模拟按键前调用
+pub fn suspend(duration: Duration) {
+    let until = now_ms() + duration.as_millis() as i64;
+    SUSPEND_UNTIL.fetch_max(until, Ordering::SeqCst);
+    reset();
+}
+
+/// 键盘监听每输入一个字符调用一次；控制字符会清空缓冲
+pub fn push_char(ch: char) {
+    if ch.is_control() || now_ms() < SUSPEND_UNTIL.load(Ordering::SeqCst) {
+        reset();
+        return;
+    }
+    let matched = {
+        let mut buffer = BUFFER.lock().unwrap();
+        buffer.push(ch);
+        if buffer.chars().count() > BUFFER_LIMIT {
+            let first = buffer.chars().next().map_or(0, char::len_utf8);
+            buffer.drain(..first);
+        }
+        let matched = find_match(&buffer);
+        if matched.is_some() {
+            buffer.clear();
+        }
+        matched
+    };
+    if let Some(abbreviation) = matched {
+        log_line(&format!("abbreviation: matched {}", abbreviation.trigger));
+        if let Some(handler) = HANDLER.get() {
+            handler(abbreviation);
+        }
+    }
+}
+
+pub fn backspace() {
+    BUFFER.lock().unwrap().pop();
+}
+
+/// 光标可能移动了（方向键、鼠标点击、快捷键），之前输入的字符不再连续
+pub fn reset() {
+    BUFFER.lock().unwrap().clear();
+}
+
+// 多个缩写同时命中时取最长的，例如 sig 和 ;sig；缩写是另一个的前缀时短的先命中
+fn find_match(buffer: &str) -> Option<Abbreviation> {
+    TRIGGERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|abbreviation| buffer.ends_with(&abbreviation.trigger))
+        .max_by_key(|abbreviation| abbreviation.trigger.len())
+        .cloned()
+}
@@ -1,77 +1,444 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::code;
+use crate::color;
+use crate::entities;
+use crate::models::{ClipboardCategory, ClipboardFormat, NewEntity};
+use crate::paths;
+use crate::rules;
+use crate::settings;
+use crate::structured::{self, StructuredInfo};
+
+/// 分类流水线的输出：一个主分类，外加任意多个标签和结构化元数据
 pub struct TextClassification {
-    pub format: String,
-    pub category: String,
+    pub format: ClipboardFormat,
+    pub category: ClipboardCategory,
     pub color: Option<String>,
     pub file_path: Option<String>,
+    pub paths: Vec<String>,
+    pub link: Option<LinkInfo>,
+    pub language: Option<String>,
+    pub structured: Option<StructuredInfo>,
+    pub custom_category: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: Map<String, Value>,
+    pub entities: Vec<NewEntity>,
+    decided: bool,
 }
 
-pub fn classify_text(input: &str) -> TextClassification {
-    let trimmed = input.trim();
-    if let Some(color) = detect_color(trimmed) {
-        return TextClassification {
-            format: "color".to_string(),
-            category: "text".to_string(),
-            color: Some(color),
+impl TextClassification {
+    fn new() -> Self {
+        Self {
+            format: ClipboardFormat::Text,
+            category: ClipboardCategory::Text,
+            color: None,
             file_path: None,
+            paths: Vec::new(),
+            link: None,
+            language: None,
+            structured: None,
+            custom_category: None,
+            tags: Vec::new(),
+            metadata: Map::new(),
+            entities: Vec::new(),
+            decided: false,
+        }
+    }
+
+    /// 主分类是否已被前面的分类器确定，后面的分类器只能追加标签和元数据
+    pub fn is_decided(&self) -> bool {
+        self.decided
+    }
+
+    pub fn decide(&mut self, format: ClipboardFormat, category: ClipboardCategory) {
+        self.format = format;
+        self.category = category;
+        self.decided = true;
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.tags.iter().any(|existing| existing == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+}
+
+/// 归一化后的链接信息，供前端按域名分组
+pub struct LinkInfo {
+    pub url: String,
+    pub host: Option<String>,
+    pub scheme: String,
+}
+
+/// 分类器的输入，语言识别结果会被多个分类器复用，按需计算一次
+pub struct ClassifyInput<'a> {
+    pub text: &'a str,
+    pub source_app: Option<&'a str>,
+    language: OnceLock<Option<&'static str>>,
+}
+
+impl<'a> ClassifyInput<'a> {
+    pub fn new(text: &'a str, source_app: Option<&'a str>) -> Self {
+        Self {
+            text,
+            source_app,
+            language: OnceLock::new(),
+        }
+    }
+
+    pub fn language(&self) -> Option<&'static str> {
+        *self
+            .language
+            .get_or_init(|| code::detect_language(self.text))
+    }
+}
+
+pub trait Classifier: Send + Sync {
+    /// 设置里用来禁用分类器的标识
+    fn id(&self) -> &'static str;
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification);
+}
+
+/// 按注册顺序依次执行的分类器列表
+#[derive(Default)]
+pub struct Pipeline {
+    classifiers: Vec<Box<dyn Classifier>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassifierInfo {
+    pub id: &'static str,
+    pub enabled: bool,
+}
+
+impl Pipeline {
+    /// 内置分类器，顺序即优先级：代码要先于链接判断，否则脚本里嵌的 URL 会让整段代码被归为链接
+    pub fn with_builtins() -> Self {
+        let mut pipeline = Self::default();
+        pipeline.register(Box::new(ColorClassifier));
+        pipeline.register(Box::new(FilePathClassifier));
+        pipeline.register(Box::new(StructuredClassifier));
+        pipeline.register(Box::new(CodeClassifier));
+        pipeline.register(Box::new(LinkClassifier));
+        pipeline.register(Box::new(EntityClassifier));
+        pipeline.register(Box::new(UserRulesClassifier));
+        pipeline
+    }
+
+    pub fn register(&mut self, classifier: Box<dyn Classifier>) {
+        self.classifiers.push(classifier);
+    }
+
+    pub fn run(&self, input: &ClassifyInput, disabled: &[String]) -> TextClassification {
+        let mut output = TextClassification::new();
+        for classifier in &self.classifiers {
+            if disabled.iter().any(|id| id == classifier.id()) {
+                continue;
+            }
+            classifier.classify(input, &mut output);
+        }
+        output
+    }
+
+    pub fn describe(&self, disabled: &[String]) -> Vec<ClassifierInfo> {
+        self.classifiers
+            .iter()
+            .map(|classifier| ClassifierInfo {
+                id: classifier.id(),
+                enabled: !disabled.iter().any(|id| id == classifier.id()),
+            })
+            .collect()
+    }
+}
+
+pub fn pipeline() -> &'static Pipeline {
+    static PIPELINE: OnceLock<Pipeline> = OnceLock::new();
+    PIPELINE.get_or_init(Pipeline::with_builtins)
+}
+
+pub fn classify_text(input: &str, source_app: Option<&str>) -> TextClassification {
+    let settings = settings::current();
+    let input = ClassifyInput::new(input.trim(), source_app);
+    pipeline().run(&input, &settings.disabled_classifiers)
+}
+
+struct ColorClassifier;
+
+impl Classifier for ColorClassifier {
+    fn id(&self) -> &'static str {
+        "color"
+    }
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        if output.is_decided() {
+            return;
+        }
+        let Some(rgba) = color::parse_color(input.text, input.source_app) else {
+            return;
         };
+        output.decide(ClipboardFormat::Color, ClipboardCategory::Text);
+        output.color = Some(rgba.to_canonical());
+        output
+            .metadata
+            .insert("rgba".to_string(), json!([rgba.r, rgba.g, rgba.b, rgba.a]));
     }
+}
 
-    if looks_like_file_path(trimmed) {
-        return TextClassification {
-            format: "file".to_string(),
-            category: "file".to_string(),
-            color: None,
-            file_path: Some(trimmed.to_string()),
+struct FilePathClassifier;
+
+impl Classifier for FilePathClassifier {
+    fn id(&self) -> &'static str {
+        "filePath"
+    }
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        if output.is_decided() {
+            return;
+        }
+        let Some(paths) = paths::parse_path_list(input.text) else {
+            return;
         };
+        output.decide(ClipboardFormat::File, ClipboardCategory::File);
+        output.file_path = Some(paths.join("\n"));
+        output.paths = paths;
     }
+}
 
-    let category = if looks_like_url(trimmed) {
+struct StructuredClassifier;
+
+impl Classifier for StructuredClassifier {
+    fn id(&self) -> &'static str {
+        "structured"
+    }
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        if output.is_decided() {
+            return;
+        }
+        let Some(data) = structured::detect_structured(input.text, input.language()) else {
+            return;
+        };
+        output.decide(ClipboardFormat::Text, ClipboardCategory::Data);
+        output.language = Some(data.format.to_string());
+        output.add_tag(data.format);
+        output.structured = Some(data);
+    }
+}
+
+struct CodeClassifier;
+
+impl Classifier for CodeClassifier {
+    fn id(&self) -> &'static str {
+        "code"
+    }
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        if output.is_decided() {
+            return;
+        }
+        let Some(language) = input.language() else {
+            return;
+        };
+        output.decide(ClipboardFormat::Text, ClipboardCategory::Code);
+        output.language = Some(language.to_string());
+        output.add_tag(language);
+    }
+}
+
+struct LinkClassifier;
+
+impl Classifier for LinkClassifier {
+    fn id(&self) -> &'static str {
         "link"
-    } else {
-        "text"
-    };
+    }
 
-    TextClassification {
-        format: "text".to_string(),
-        category: category.to_string(),
-        color: None,
-        file_path: None,
+    // 已经归为代码或数据的文本里出现链接时，只记录链接信息和标签，不改变主分类
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        if output.format != ClipboardFormat::Text {
+            return;
+        }
+        let Some(link) = detect_link(input.text) else {
+            return;
+        };
+        if output.is_decided() {
+            output.add_tag("link");
+        } else {
+            output.decide(ClipboardFormat::Text, ClipboardCategory::Link);
+        }
+        output.link = Some(link);
     }
 }
 
-pub fn looks_like_url(text: &str) -> bool {
-    let lower = text.to_ascii_lowercase();
-    lower.starts_with("http://") || lower.starts_with("https://")
+struct EntityClassifier;
+
+impl Classifier for EntityClassifier {
+    fn id(&self) -> &'static str {
+        "entities"
+    }
+
+    // 实体单独存表，不影响主分类
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        output.entities = entities::extract_entities(input.text);
+    }
 }
 
-pub fn looks_like_file_path(text: &str) -> bool {
-    let trimmed = text.trim();
-    if trimmed.starts_with("\\\\") {
-        return true;
+struct UserRulesClassifier;
+
+impl Classifier for UserRulesClassifier {
+    fn id(&self) -> &'static str {
+        "userRules"
     }
-    if trimmed.len() >= 3 {
-        let bytes = trimmed.as_bytes();
-        if bytes[1] == b':' && (bytes[2] == b'\\' || bytes[2] == b'/') {
-            return true;
+
+    fn classify(&self, input: &ClassifyInput, output: &mut TextClassification) {
+        let matched = rules::apply(Some(input.text), input.source_app);
+        if output.custom_category.is_none() {
+            output.custom_category = matched.category;
+        }
+        for tag in &matched.tags {
+            output.add_tag(tag);
         }
     }
-    trimmed.starts_with('/')
 }
 
-pub fn detect_color(text: &str) -> Option<String> {
+const LINK_SCHEMES: &[&str] = &[
+    "http", "https", "ftp", "ftps", "sftp", "ssh", "git", "file", "mailto",
+];
+
+// 无协议的裸域名只在这些常见后缀下才认为是链接，避免把 readme.md 之类当成域名
+const COMMON_TLDS: &[&str] = &[
+    "com", "org", "net", "io", "dev", "app", "ai", "co", "me", "info", "biz", "edu", "gov", "cn",
+    "jp", "uk", "de", "fr", "ru", "us", "xyz", "tech", "site", "cloud",
+];
+
+/// 识别文本中的链接：整段文本是链接，或长文本中嵌入的第一个链接
+pub fn detect_link(text: &str) -> Option<LinkInfo> {
     let trimmed = text.trim();
-    let hex = trimmed.strip_prefix('#')?;
-    let len = hex.len();
-    if len != 3 && len != 4 && len != 6 && len != 8 {
+    if trimmed.is_empty() {
+        return None;
+    }
+    if !trimmed.contains(char::is_whitespace) {
+        return parse_link_token(trimmed, true);
+    }
+
+    trimmed
+        .split_whitespace()
+        .map(trim_link_punctuation)
+        .find_map(|token| parse_link_token(token, false))
+}
+
+fn trim_link_punctuation(token: &str) -> &str {
+    let token = token.trim_start_matches(['(', '[', '{', '<', '"', '\'', '“', '‘', '（', '「']);
+    let token = token.trim_end_matches([
+        '.', ',', ';', ':', '!', '?', '"', '\'', '>', '”', '’', '，', '。', '；', '！', '？', '）',
+        '」',
+    ]);
+    // 只有括号不成对时才去掉结尾的右括号，保留 wiki 风格的 /Foo_(bar)
+    let mut token = token;
+    for (open, close) in [('(', ')'), ('[', ']'), ('{', '}')] {
+        if token.ends_with(close) && token.matches(open).count() < token.matches(close).count() {
+            token = &token[..token.len() - 1];
+        }
+    }
+    token
+}
+
+fn parse_link_token(token: &str, whole_text: bool) -> Option<LinkInfo> {
+    if token.is_empty() {
+        return None;
+    }
+    let lower = token.to_lowercase();
+
+    if let Some((scheme, _)) = lower.split_once(':') {
+        if LINK_SCHEMES.contains(&scheme) {
+            return parse_url(token);
+        }
+    }
+
+    if let Some(normalized) = scp_like_remote(token) {
+        return parse_url(&normalized);
+    }
+
+    let bare = bare_domain_regex().captures(token)?;
+    let tld = bare.name("tld")?.as_str().to_lowercase();
+    let has_rest = bare.name("rest").is_some_and(|m| !m.as_str().is_empty());
+    let has_www = lower.starts_with("www.");
+    // 嵌入在长文本中的裸域名要求更严格，必须带 www. 或路径
+    let accepted = if whole_text {
+        has_www || has_rest || COMMON_TLDS.contains(&tld.as_str()) || !tld.is_ascii()
+    } else {
+        has_www || (has_rest && (COMMON_TLDS.contains(&tld.as_str()) || !tld.is_ascii()))
+    };
+    if !accepted {
         return None;
     }
-    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Some(trimmed.to_string());
+    parse_url(&format!("https://{token}"))
+}
+
+// git@github.com:owner/repo.git 这类 scp 风格的远程地址
+fn scp_like_remote(token: &str) -> Option<String> {
+    static SCP: OnceLock<Regex> = OnceLock::new();
+    let re = SCP.get_or_init(|| {
+        Regex::new(concat!(
+            r"^(?P<user>[A-Za-z0-9._-]+)@",
+            r"(?P<host>[A-Za-z0-9.-]+\.[A-Za-z]{2,}|localhost):",
+            r"(?P<path>[^/\s][^\s]*)$",
+        ))
+        .unwrap()
+    });
+    let caps = re.captures(token)?;
+    Some(format!(
+        "ssh://{}@{}/{}",
+        &caps["user"], &caps["host"], &caps["path"]
+    ))
+}
+
+fn bare_domain_regex() -> &'static Regex {
+    static BARE: OnceLock<Regex> = OnceLock::new();
+    BARE.get_or_init(|| {
+        Regex::new(
+            r"^(?:[\p{L}\p{N}](?:[\p{L}\p{N}-]{0,61}[\p{L}\p{N}])?\.)+(?P<tld>\p{L}{2,24})(?::\d{1,5})?(?P<rest>[/?#]\S*)?$",
+        )
+        .unwrap()
+    })
+}
+
+fn parse_url(candidate: &str) -> Option<LinkInfo> {
+    let url = url::Url::parse(candidate).ok()?;
+    let scheme = url.scheme().to_string();
+    let host = match scheme.as_str() {
+        "mailto" => {
+            let address = url.path();
+            let (_, domain) = address.rsplit_once('@')?;
+            if domain.is_empty() {
+                return None;
+            }
+            Some(url::quirks::domain_to_unicode(&domain.to_lowercase()))
+        }
+        "file" => unicode_host(&url),
+        _ => Some(unicode_host(&url)?),
+    };
+    Some(LinkInfo {
+        url: url.to_string(),
+        host,
+        scheme,
+    })
+}
+
+// 国际化域名按 Unicode 形式保存，和用户看到、搜索的写法一致；url 本身保留 punycode
+fn unicode_host(url: &url::Url) -> Option<String> {
+    match url.host()? {
+        url::Host::Domain("") => None,
+        url::Host::Domain(domain) => Some(url::quirks::domain_to_unicode(domain)),
+        _ => url.host_str().map(str::to_string),
     }
-    None
 }
 
-#[allow(dead_code)]
 pub fn strip_html(input: &str) -> String {
     let mut output = String::with_capacity(input.len());
     let mut in_tag = false;
@@ -85,3 +452,108 @@ pub fn strip_html(input: &str) -> String {
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(text: &str) -> Option<(String, Option<String>, String)> {
+        detect_link(text).map(|link| (link.url, link.host, link.scheme))
+    }
+
+    #[test]
+    fn detects_links_with_scheme() {
+        let cases = [
+            (
+                "https://Example.com/a?b=1",
+                "https://example.com/a?b=1",
+                Some("example.com"),
+                "https",
+            ),
+            (
+                "ftp://files.example.org",
+                "ftp://files.example.org/",
+                Some("files.example.org"),
+                "ftp",
+            ),
+            (
+                "mailto:Bob@Example.COM",
+                "mailto:Bob@Example.COM",
+                Some("example.com"),
+                "mailto",
+            ),
+            ("file:///tmp/a.txt", "file:///tmp/a.txt", None, "file"),
+            (
+                "http://127.0.0.1:8080/x",
+                "http://127.0.0.1:8080/x",
+                Some("127.0.0.1"),
+                "http",
+            ),
+        ];
+        for (text, url, host, scheme) in cases {
+            assert_eq!(
+                link(text),
+                Some((
+                    url.to_string(),
+                    host.map(str::to_string),
+                    scheme.to_string()
+                )),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_schemes() {
+        assert!(link("javascript:alert(1)").is_none());
+        assert!(link("c:/Users/me").is_none());
+        assert!(link("mailto:nobody").is_none());
+    }
+
+    #[test]
+    fn converts_scp_remotes() {
+        let (url, host, scheme) = link("git@github.com:owner/repo.git").unwrap();
+        assert_eq!(url, "ssh://git@github.com/owner/repo.git");
+        assert_eq!(host.as_deref(), Some("github.com"));
+        assert_eq!(scheme, "ssh");
+    }
+
+    #[test]
+    fn bare_domains_use_tld_heuristics() {
+        assert_eq!(link("example.com").unwrap().0, "https://example.com/");
+        assert_eq!(link("www.example.zz").unwrap().0, "https://www.example.zz/");
+        assert_eq!(link("docs.rs/regex").unwrap().0, "https://docs.rs/regex");
+        // 不常见后缀的单个词更可能是文件名
+        assert!(link("readme.md").is_none());
+        assert!(link("config.yaml").is_none());
+    }
+
+    #[test]
+    fn embedded_links_need_scheme_www_or_path() {
+        assert_eq!(
+            link("see (https://example.com/a_(b)).").unwrap().0,
+            "https://example.com/a_(b)"
+        );
+        assert_eq!(
+            link("docs at www.example.com, thanks").unwrap().0,
+            "https://www.example.com/"
+        );
+        assert_eq!(
+            link("read github.com/rust-lang/rust now").unwrap().0,
+            "https://github.com/rust-lang/rust"
+        );
+        assert!(link("open example.com now").is_none());
+        assert!(link("edit main.rs and lib.rs/x").is_none());
+    }
+
+    #[test]
+    fn idn_hosts_are_stored_as_unicode() {
+        let (url, host, _) = link("https://münchen.de/karte").unwrap();
+        assert_eq!(url, "https://xn--mnchen-3ya.de/karte");
+        assert_eq!(host.as_deref(), Some("münchen.de"));
+        let (_, host, _) = link("https://xn--mnchen-3ya.de").unwrap();
+        assert_eq!(host.as_deref(), Some("münchen.de"));
+        let (_, host, _) = link("例子.中国").unwrap();
+        assert_eq!(host.as_deref(), Some("例子.中国"));
+    }
+}
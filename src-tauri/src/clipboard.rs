@@ -2,11 +2,12 @@ use std::collections::hash_map::DefaultHasher;
 use std::fs::OpenOptions;
 use std::hash::{Hash, Hasher};
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
 use sqlx::SqlitePool;
 use tauri::{AppHandle, Emitter};
 
@@ -31,15 +32,86 @@ use windows::Win32::UI::WindowsAndMessaging::{
     TranslateMessage, HWND_MESSAGE, MSG, WM_CLIPBOARDUPDATE, WNDCLASSW,
 };
 
-use crate::{classify, db, models::NewClipboardItem};
+use crate::models::{ClipboardFormat, NewClipboardItem};
+use crate::phash::{self, ImageDuplicatePolicy};
+use crate::{classify, db, foreground, ocr, paste_queue, paths, rules, settings};
 
 struct CapturedItem {
     item: NewClipboardItem,
     hash: u64,
 }
 
+/// 从剪贴板读到、还没分类的内容；哈希和上次相同时直接丢弃，不做后面的耗时处理
+struct RawCapture {
+    content: RawContent,
+    hash: u64,
+}
+
+enum RawContent {
+    Text(String),
+    Image {
+        bytes: Vec<u8>,
+        width: usize,
+        height: usize,
+    },
+}
+
+impl RawCapture {
+    // 查找来源程序、分类、检查路径是否存在等处理放到确认内容有变化之后
+    fn process(self) -> Option<CapturedItem> {
+        let source_app = foreground::foreground_app();
+        match self.content {
+            RawContent::Text(text) => text_capture(&text, source_app),
+            RawContent::Image {
+                bytes,
+                width,
+                height,
+            } => image_capture(bytes, width, height, source_app),
+        }
+    }
+}
+
 static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
 
+// 在这个时间点（毫秒）之前的剪贴板变化都是应用自己写入的，不记录
+static SUPPRESS_UNTIL: AtomicI64 = AtomicI64::new(0);
+
+/// 粘贴后恢复原剪贴板内容的默认延迟
+pub const DEFAULT_RESTORE_DELAY_MS: u64 = 500;
+
+/// 粘贴前保存的剪贴板内容
+enum ClipboardSnapshot {
+    Empty,
+    Files(Vec<PathBuf>),
+    Image(ImageData<'static>),
+    Html {
+        html: String,
+        alt_text: Option<String>,
+    },
+    Text(String),
+}
+
+struct PendingRestore {
+    generation: u64,
+    snapshot: ClipboardSnapshot,
+}
+
+// 应用自己写入剪贴板的内容，监听到时不新增记录，只更新原记录的使用时间
+struct SelfWrite {
+    hash: u64,
+    item_id: Option<i64>,
+    written_at: i64,
+}
+
+static SELF_WRITES: Mutex<Vec<SelfWrite>> = Mutex::new(Vec::new());
+
+// 超过这个时间还没被监听到的写入标记作废
+const SELF_WRITE_TTL_MS: i64 = 5_000;
+
+// 等待恢复的原始内容；连续粘贴时保留最早的那份，避免把粘贴出去的内容当成原内容
+static PENDING_RESTORE: Mutex<Option<PendingRestore>> = Mutex::new(None);
+static RESTORE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 pub fn init_logger(path: PathBuf) {
     let _ = LOG_PATH.set(path.clone());
     log_line(&format!("logger initialized: {}", path.display()));
@@ -76,8 +148,8 @@ fn start_polling_windows(app_handle: AppHandle, pool: SqlitePool) {
         eprintln!("clipboard: fallback polling started");
         let mut last_hash: Option<u64> = None;
         loop {
-            if let Some(captured) = capture_clipboard_with_retry() {
-                handle_captured(&mut last_hash, &app_handle, &pool, captured);
+            if let Some(raw) = capture_clipboard_with_retry() {
+                handle_captured(&mut last_hash, &app_handle, &pool, raw);
             }
             std::thread::sleep(Duration::from_millis(500));
         }
@@ -89,8 +161,8 @@ fn start_polling_async(app_handle: AppHandle, pool: SqlitePool) {
     tauri::async_runtime::spawn(async move {
         let mut last_hash: Option<u64> = None;
         loop {
-            if let Some(captured) = capture_clipboard() {
-                handle_captured(&mut last_hash, &app_handle, &pool, captured);
+            if let Some(raw) = capture_clipboard() {
+                handle_captured(&mut last_hash, &app_handle, &pool, raw);
             }
             sleep(Duration::from_millis(500)).await;
         }
@@ -101,66 +173,267 @@ fn handle_captured(
     last_hash: &mut Option<u64>,
     app_handle: &AppHandle,
     pool: &SqlitePool,
-    captured: CapturedItem,
+    raw: RawCapture,
 ) {
-    if *last_hash == Some(captured.hash) {
+    if *last_hash == Some(raw.hash) {
         return;
     }
-    *last_hash = Some(captured.hash);
+    *last_hash = Some(raw.hash);
+    if let Some(item_id) = take_self_write(raw.hash) {
+        log_line("clipboard: change written by app, updating last used time");
+        if let Some(item_id) = item_id {
+            let pool = pool.clone();
+            let handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                match db::touch_item(&pool, item_id, now_ms()).await {
+                    Ok(()) => emit_updated(&handle),
+                    Err(err) => log_line(&format!("failed to update last used time: {err}")),
+                }
+            });
+        }
+        return;
+    }
+    if now_ms() < SUPPRESS_UNTIL.load(Ordering::SeqCst) {
+        log_line("clipboard: change written by app, skipping");
+        return;
+    }
+    let Some(captured) = raw.process() else {
+        return;
+    };
     log_line(&format!("clipboard: captured item hash={}", captured.hash));
     let pool = pool.clone();
     let handle = app_handle.clone();
     tauri::async_runtime::spawn(async move {
-        // 检查数据库中最新的记录是否与当前内容一致
-        match db::get_latest_item(&pool).await {
-            Ok(Some(latest)) => {
-                // 比较内容是否一致
-                let is_duplicate = match captured.item.format.as_str() {
-                    "image" => {
-                        // 图片比较：比较宽度、高度和图片数据
-                        latest.image == captured.item.image
-                            && latest.image_width == captured.item.image_width
-                            && latest.image_height == captured.item.image_height
-                    }
-                    _ => {
-                        // 文本类内容比较：比较 text、html、file_path、color
-                        latest.text == captured.item.text
-                            && latest.html == captured.item.html
-                            && latest.file_path == captured.item.file_path
-                            && latest.color == captured.item.color
-                    }
-                };
-
-                if is_duplicate {
-                    log_line("clipboard: duplicate content, skipping insert");
-                    return;
-                }
+        let id = match store_captured(&pool, &handle, captured).await {
+            Ok(id) => id,
+            Err(err) => {
+                log_line(&format!("failed to store clipboard item: {err}"));
+                eprintln!("failed to store clipboard item: {err}");
+                return;
             }
-            Ok(None) => {
-                // 数据库为空，直接插入
+        };
+        if paste_queue::is_collecting() {
+            match db::get_item(&pool, id).await {
+                Ok(Some(row)) => paste_queue::collect(&handle, row),
+                Ok(None) => {}
+                Err(err) => log_line(&format!("failed to load item for paste queue: {err}")),
             }
-            Err(err) => {
-                log_line(&format!("failed to get latest item: {err}"));
-                eprintln!("failed to get latest item: {err}");
-                // 查询失败时仍然尝试插入
+        }
+    });
+}
+
+/// 去重、合并后写入数据库，返回最终对应的记录 id（重复时为已有记录）
+async fn store_captured(
+    pool: &SqlitePool,
+    handle: &AppHandle,
+    captured: CapturedItem,
+) -> Result<i64, sqlx::Error> {
+    // 检查数据库中最新的记录是否与当前内容一致
+    match db::get_latest_item(pool).await {
+        Ok(Some(latest)) => {
+            // 比较内容是否一致
+            let is_duplicate = match captured.item.format {
+                ClipboardFormat::Image => {
+                    // 图片比较：比较宽度、高度和图片数据
+                    latest.image == captured.item.image
+                        && latest.image_width == captured.item.image_width
+                        && latest.image_height == captured.item.image_height
+                }
+                _ => {
+                    // 文本类内容比较：比较 text、html、file_path、color
+                    latest.text == captured.item.text
+                        && latest.html == captured.item.html
+                        && latest.file_path == captured.item.file_path
+                        && latest.color == captured.item.color
+                }
+            };
+
+            if is_duplicate {
+                log_line("clipboard: duplicate content, skipping insert");
+                return Ok(latest.id);
+            }
+        }
+        Ok(None) => {
+            // 数据库为空，直接插入
+        }
+        Err(err) => {
+            log_line(&format!("failed to get latest item: {err}"));
+            eprintln!("failed to get latest item: {err}");
+            // 查询失败时仍然尝试插入
+        }
+    }
+
+    // 与最近的截图近似重复时按设置跳过或合并
+    let settings = settings::current();
+    if let (ClipboardFormat::Image, Some(hash)) = (captured.item.format, captured.item.image_phash)
+    {
+        let policy = settings.image_duplicate_policy;
+        let threshold = settings
+            .image_duplicate_threshold
+            .unwrap_or(phash::DEFAULT_THRESHOLD);
+        if policy != ImageDuplicatePolicy::Keep {
+            match phash::find_near_duplicate(pool, hash as u64, threshold).await {
+                Ok(Some(id)) if policy == ImageDuplicatePolicy::Skip => {
+                    log_line(&format!("clipboard: near-duplicate of item {id}, skipping"));
+                    return Ok(id);
+                }
+                Ok(Some(id)) => {
+                    log_line(&format!("clipboard: near-duplicate of item {id}, merging"));
+                    db::merge_image_duplicate(pool, id, captured.item).await?;
+                    ocr::wake();
+                    emit_updated(handle);
+                    return Ok(id);
+                }
+                Ok(None) => {}
+                Err(err) => log_line(&format!("failed to load image hashes: {err}")),
             }
         }
+    }
+
+    let is_image = captured.item.format == ClipboardFormat::Image;
+    let id = db::insert_item(pool, captured.item).await?;
+    if is_image {
+        ocr::wake();
+    }
+    emit_updated(handle);
+    Ok(id)
+}
+
+pub fn emit_updated(handle: &AppHandle) {
+    match handle.emit("clipboard://updated", ()) {
+        Ok(_) => log_line("clipboard: event emitted"),
+        Err(err) => log_line(&format!("clipboard: event emit failed: {err}")),
+    }
+}
+
+/// 写入文本之前调用，监听到这次写入时更新 item_id 的使用时间而不是新增记录
+pub fn mark_text_written(text: &str, item_id: Option<i64>) {
+    mark_self_write(hash_text(text.trim()), item_id);
+}
+
+pub fn mark_image_written(bytes: &[u8], width: usize, height: usize, item_id: Option<i64>) {
+    mark_self_write(hash_image(bytes, width, height), item_id);
+}
+
+fn mark_self_write(hash: u64, item_id: Option<i64>) {
+    let now = now_ms();
+    let mut writes = SELF_WRITES.lock().unwrap();
+    writes.retain(|write| now - write.written_at < SELF_WRITE_TTL_MS && write.hash != hash);
+    writes.push(SelfWrite {
+        hash,
+        item_id,
+        written_at: now,
+    });
+}
+
+fn take_self_write(hash: u64) -> Option<Option<i64>> {
+    let now = now_ms();
+    let mut writes = SELF_WRITES.lock().unwrap();
+    writes.retain(|write| now - write.written_at < SELF_WRITE_TTL_MS);
+    let index = writes.iter().position(|write| write.hash == hash)?;
+    Some(writes.remove(index).item_id)
+}
 
-        if let Err(err) = db::insert_item(&pool, captured.item).await {
-            log_line(&format!("failed to insert clipboard item: {err}"));
-            eprintln!("failed to insert clipboard item: {err}");
-            return;
+/// 接下来一段时间内的剪贴板变化不记录为新记录
+pub fn suppress_capture(duration: Duration) {
+    let until = now_ms() + duration.as_millis() as i64;
+    SUPPRESS_UNTIL.fetch_max(until, Ordering::SeqCst);
+}
+
+/// 写入剪贴板之前保存当前内容，返回给 schedule_restore 使用的序号
+pub fn snapshot_for_restore() -> u64 {
+    let generation = RESTORE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let mut pending = PENDING_RESTORE.lock().unwrap();
+    match pending.as_mut() {
+        // 上一次粘贴还没恢复，剪贴板里是上次粘贴的内容，沿用之前保存的原内容
+        Some(pending) => pending.generation = generation,
+        None => {
+            *pending = Some(PendingRestore {
+                generation,
+                snapshot: take_snapshot(),
+            })
         }
-        match handle.emit("clipboard://updated", ()) {
-            Ok(_) => log_line("clipboard: event emitted"),
-            Err(err) => log_line(&format!("clipboard: event emit failed: {err}")),
+    }
+    generation
+}
+
+/// 延迟一段时间后恢复 snapshot_for_restore 保存的内容；期间又有新的粘贴时交给最新的一次恢复
+pub fn schedule_restore(generation: u64, delay: Duration) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let snapshot = {
+            let mut pending = PENDING_RESTORE.lock().unwrap();
+            if pending.as_ref().map(|pending| pending.generation) != Some(generation) {
+                return;
+            }
+            pending.take().map(|pending| pending.snapshot)
+        };
+        if let Some(snapshot) = snapshot {
+            mark_restore(&snapshot);
+            match restore_snapshot(snapshot) {
+                Ok(()) => log_line("clipboard: previous contents restored"),
+                Err(err) => log_line(&format!("clipboard: restore failed: {err}")),
+            }
         }
     });
 }
 
-const MAX_IMAGE_SIZE: usize = 20 * 1024 * 1024;
+fn take_snapshot() -> ClipboardSnapshot {
+    let Ok(mut clipboard) = Clipboard::new() else {
+        return ClipboardSnapshot::Empty;
+    };
+    if let Ok(files) = clipboard.get().file_list() {
+        if !files.is_empty() {
+            return ClipboardSnapshot::Files(files);
+        }
+    }
+    if let Ok(image) = clipboard.get_image() {
+        return ClipboardSnapshot::Image(image);
+    }
+    let text = clipboard.get_text().ok();
+    if let Ok(html) = clipboard.get().html() {
+        return ClipboardSnapshot::Html {
+            html,
+            alt_text: text,
+        };
+    }
+    match text {
+        Some(text) => ClipboardSnapshot::Text(text),
+        None => ClipboardSnapshot::Empty,
+    }
+}
+
+// 恢复本身也会触发一次剪贴板变化，按自己写入的内容跳过，不影响这段时间里用户自己的复制
+fn mark_restore(snapshot: &ClipboardSnapshot) {
+    match snapshot {
+        ClipboardSnapshot::Text(text)
+        | ClipboardSnapshot::Html {
+            alt_text: Some(text),
+            ..
+        } => mark_text_written(text, None),
+        ClipboardSnapshot::Image(image) => {
+            mark_image_written(&image.bytes, image.width, image.height, None)
+        }
+        // 文件列表在部分平台上能读出路径文本，算不出对应的指纹，只能短暂跳过
+        ClipboardSnapshot::Files(_) => suppress_capture(Duration::from_millis(1000)),
+        ClipboardSnapshot::Html { alt_text: None, .. } | ClipboardSnapshot::Empty => {}
+    }
+}
+
+fn restore_snapshot(snapshot: ClipboardSnapshot) -> Result<(), arboard::Error> {
+    let mut clipboard = Clipboard::new()?;
+    match snapshot {
+        ClipboardSnapshot::Empty => clipboard.clear(),
+        ClipboardSnapshot::Files(files) => clipboard.set().file_list(&files),
+        ClipboardSnapshot::Image(image) => clipboard.set_image(image),
+        ClipboardSnapshot::Html { html, alt_text } => clipboard.set_html(html, alt_text),
+        ClipboardSnapshot::Text(text) => clipboard.set_text(text),
+    }
+}
+
+pub const MAX_IMAGE_SIZE: usize = 20 * 1024 * 1024;
 
-fn capture_clipboard() -> Option<CapturedItem> {
+fn capture_clipboard() -> Option<RawCapture> {
     let mut clipboard = match Clipboard::new() {
         Ok(clipboard) => clipboard,
         Err(err) => {
@@ -173,23 +446,21 @@ fn capture_clipboard() -> Option<CapturedItem> {
     if let Ok(image) = clipboard.get_image() {
         let bytes = image.bytes.into_owned();
         if bytes.len() > MAX_IMAGE_SIZE {
-            log_line(&format!("clipboard: image too large ({} bytes), skipping", bytes.len()));
+            log_line(&format!(
+                "clipboard: image too large ({} bytes), skipping",
+                bytes.len()
+            ));
             return None;
         }
-        let hash = hash_image(&bytes, image.width, image.height);
-        let item = NewClipboardItem {
-            format: "image".to_string(),
-            category: "image".to_string(),
-            text: None,
-            html: None,
-            file_path: None,
-            color: None,
-            image: Some(bytes),
-            image_width: Some(image.width as i64),
-            image_height: Some(image.height as i64),
-            created_at: now_ms(),
-        };
-        return Some(CapturedItem { item, hash });
+        // 指纹要缩放整张图片，等确认是新图片后再算
+        return Some(RawCapture {
+            hash: hash_image(&bytes, image.width, image.height),
+            content: RawContent::Image {
+                bytes,
+                width: image.width,
+                height: image.height,
+            },
+        });
     }
 
     if let Ok(text) = clipboard.get_text() {
@@ -197,28 +468,146 @@ fn capture_clipboard() -> Option<CapturedItem> {
         if trimmed.is_empty() {
             return None;
         }
-        let classification = classify::classify_text(trimmed);
-        let hash = hash_text(trimmed);
-        let item = NewClipboardItem {
-            format: classification.format,
-            category: classification.category,
-            text: Some(trimmed.to_string()),
-            html: None,
-            file_path: classification.file_path,
-            color: classification.color,
-            image: None,
-            image_width: None,
-            image_height: None,
-            created_at: now_ms(),
-        };
-        return Some(CapturedItem { item, hash });
+        return Some(RawCapture {
+            hash: hash_text(trimmed),
+            content: RawContent::Text(trimmed.to_string()),
+        });
     }
 
     None
 }
 
+fn text_capture(text: &str, source_app: Option<String>) -> Option<CapturedItem> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let classification = classify::classify_text(trimmed, source_app.as_deref());
+    let hash = hash_text(trimmed);
+    let link = classification.link;
+    let structured = classification.structured;
+    let item = NewClipboardItem {
+        format: classification.format,
+        category: classification.category,
+        text: Some(trimmed.to_string()),
+        html: None,
+        file_path: classification.file_path,
+        color: classification.color,
+        link_url: link.as_ref().map(|link| link.url.clone()),
+        link_host: link.as_ref().and_then(|link| link.host.clone()),
+        link_scheme: link.map(|link| link.scheme),
+        language: classification.language,
+        data_format: structured.as_ref().map(|data| data.format.to_string()),
+        data_valid: structured.as_ref().map(|data| data.valid),
+        data_shape: structured.map(|data| data.shape.to_string()),
+        source_app,
+        custom_category: classification.custom_category,
+        tags: encode_tags(&classification.tags),
+        metadata: encode_metadata(&classification.metadata),
+        image_phash: None,
+        image: None,
+        image_width: None,
+        image_height: None,
+        created_at: now_ms(),
+        entities: classification.entities,
+        paths: classification
+            .paths
+            .iter()
+            .map(|path| paths::inspect(path, false, now_ms()))
+            .collect(),
+    };
+    Some(CapturedItem { item, hash })
+}
+
+fn image_capture(
+    bytes: Vec<u8>,
+    width: usize,
+    height: usize,
+    source_app: Option<String>,
+) -> Option<CapturedItem> {
+    if bytes.len() > MAX_IMAGE_SIZE {
+        log_line(&format!(
+            "clipboard: image too large ({} bytes), skipping",
+            bytes.len()
+        ));
+        return None;
+    }
+    let hash = hash_image(&bytes, width, height);
+    let image_phash = phash::dhash(&bytes, width as u32, height as u32);
+    let matched = if settings::current().classifier_enabled("userRules") {
+        rules::apply(None, source_app.as_deref())
+    } else {
+        rules::RuleMatch::default()
+    };
+    let mut item =
+        NewClipboardItem::image(bytes, width as i64, height as i64, image_phash, now_ms());
+    item.source_app = source_app;
+    item.custom_category = matched.category;
+    item.tags = encode_tags(&matched.tags);
+    Some(CapturedItem { item, hash })
+}
+
+/// 把磁盘上的图片文件导入为历史记录，与剪贴板捕获走同一套去重和分类流程
+pub async fn import_image_file(
+    handle: &AppHandle,
+    pool: &SqlitePool,
+    path: &Path,
+) -> Result<i64, String> {
+    let path = path.to_path_buf();
+    let captured = tauri::async_runtime::spawn_blocking(move || {
+        let open = || {
+            image::ImageReader::open(&path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|err| format!("打开图片失败: {err}"))
+        };
+        // 解码前先按尺寸检查，避免超大图片占满内存
+        let (width, height) = open()?
+            .into_dimensions()
+            .map_err(|err| format!("无法识别的图片格式: {err}"))?;
+        if width as usize * height as usize * 4 > MAX_IMAGE_SIZE {
+            return Err(format!("图片过大: {width}x{height}"));
+        }
+        let image = open()?
+            .decode()
+            .map_err(|err| format!("解码图片失败: {err}"))?
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        let mut captured = image_capture(image.into_raw(), width as usize, height as usize, None)
+            .ok_or_else(|| "图片过大".to_string())?;
+        captured.item.metadata =
+            Some(serde_json::json!({ "importedFrom": path.to_string_lossy() }).to_string());
+        Ok(captured)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    store_captured(pool, handle, captured)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 把应用内生成的文本（转换、合并结果等）保存为新记录，extra 会合并进 metadata
+pub async fn save_text(
+    handle: &AppHandle,
+    pool: &SqlitePool,
+    text: &str,
+    extra: serde_json::Map<String, serde_json::Value>,
+) -> Result<i64, String> {
+    let mut captured = text_capture(text, None).ok_or_else(|| "内容为空".to_string())?;
+    let mut metadata: serde_json::Map<String, serde_json::Value> = captured
+        .item
+        .metadata
+        .as_deref()
+        .and_then(|metadata| serde_json::from_str(metadata).ok())
+        .unwrap_or_default();
+    metadata.extend(extra);
+    captured.item.metadata = encode_metadata(&metadata);
+    store_captured(pool, handle, captured)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[cfg(target_os = "windows")]
-fn capture_clipboard_with_retry() -> Option<CapturedItem> {
+fn capture_clipboard_with_retry() -> Option<RawCapture> {
     for attempt in 0..5 {
         if let Some(captured) = capture_clipboard() {
             return Some(captured);
@@ -230,7 +619,21 @@ fn capture_clipboard_with_retry() -> Option<CapturedItem> {
     None
 }
 
-fn now_ms() -> i64 {
+fn encode_tags(tags: &[String]) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+    serde_json::to_string(tags).ok()
+}
+
+fn encode_metadata(metadata: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+    if metadata.is_empty() {
+        return None;
+    }
+    serde_json::to_string(metadata).ok()
+}
+
+pub fn now_ms() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_millis() as i64)
@@ -305,9 +708,9 @@ fn run_clipboard_listener(app_handle: AppHandle, pool: SqlitePool) -> windows::c
         }
         if msg.message == WM_CLIPBOARDUPDATE {
             log_line("clipboard: WM_CLIPBOARDUPDATE received");
-            let captured = capture_clipboard_with_retry();
-            if let Some(captured) = captured {
-                handle_captured(&mut last_hash, &app_handle, &pool, captured);
+            let raw = capture_clipboard_with_retry();
+            if let Some(raw) = raw {
+                handle_captured(&mut last_hash, &app_handle, &pool, raw);
             } else {
                 log_line("clipboard update received but no data captured");
                 eprintln!("clipboard update received but no data captured");
@@ -338,7 +741,7 @@ unsafe extern "system" fn window_proc(
     DefWindowProcW(hwnd, msg, wparam, lparam)
 }
 
-fn log_line(message: &str) {
+pub fn log_line(message: &str) {
     if let Some(path) = LOG_PATH.get() {
         let line = format!("{} {}\n", now_ms(), message);
         if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
@@ -0,0 +1,268 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+// 低于这个分数的文本按普通文本处理，避免把带标点的自然语言误判成代码
+const MIN_SCORE: u32 = 4;
+
+struct LanguageRules {
+    name: &'static str,
+    patterns: Vec<(Regex, u32)>,
+}
+
+/// 离线的代码语言猜测，基于每种语言的特征正则打分
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let trimmed = text.trim();
+    if trimmed.len() < 4 {
+        return None;
+    }
+    if looks_like_json(trimmed) {
+        return Some("json");
+    }
+    if looks_like_html(trimmed) {
+        return Some("html");
+    }
+
+    let mut best: Option<(&'static str, u32)> = None;
+    for rules in language_rules() {
+        let score: u32 = rules
+            .patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.is_match(trimmed))
+            .map(|(_, weight)| weight)
+            .sum();
+        if score >= MIN_SCORE && best.is_none_or(|(_, top)| score > top) {
+            best = Some((rules.name, score));
+        }
+    }
+    if let Some((name, _)) = best {
+        return Some(name);
+    }
+
+    if looks_like_yaml(trimmed) {
+        return Some("yaml");
+    }
+    None
+}
+
+fn looks_like_json(text: &str) -> bool {
+    let starts = text.starts_with('{') || text.starts_with('[');
+    let ends = text.ends_with('}') || text.ends_with(']');
+    starts && ends && serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+fn looks_like_html(text: &str) -> bool {
+    static CLOSING_TAG: OnceLock<Regex> = OnceLock::new();
+    if !text.starts_with('<') {
+        return false;
+    }
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return true;
+    }
+    let closing = CLOSING_TAG.get_or_init(|| Regex::new(r"</[A-Za-z][A-Za-z0-9-]*\s*>").unwrap());
+    closing.is_match(text) && text.ends_with('>')
+}
+
+// YAML 的判定放在最后：大部分行是 key: value 或列表项，并且不像其他语言
+fn looks_like_yaml(text: &str) -> bool {
+    static LINE: OnceLock<Regex> = OnceLock::new();
+    let line_re = LINE
+        .get_or_init(|| Regex::new(r#"^\s*(?:-\s+\S|-\s*$|[\w."'-]+:(?:\s|$)|#|---$)"#).unwrap());
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    let has_mapping = lines.iter().any(|l| l.contains(": ") || l.ends_with(':'));
+    // 需要有嵌套或列表结构，否则 "Note: xxx" 这样的备忘也会被当成 YAML
+    let has_structure = lines
+        .iter()
+        .any(|l| l.starts_with([' ', '\t']) || l.starts_with("- ") || *l == "---");
+    let matching = lines.iter().filter(|l| line_re.is_match(l)).count();
+    has_mapping && has_structure && matching * 10 >= lines.len() * 8 && !text.contains(';')
+}
+
+fn language_rules() -> &'static [LanguageRules] {
+    static RULES: OnceLock<Vec<LanguageRules>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let build = |name: &'static str, patterns: &[(&str, u32)]| LanguageRules {
+            name,
+            patterns: patterns
+                .iter()
+                .map(|(pattern, weight)| (Regex::new(pattern).unwrap(), *weight))
+                .collect(),
+        };
+        vec![
+            build(
+                "rust",
+                &[
+                    (r"\bfn\s+\w+\s*[<(]", 3),
+                    (r"\blet\s+mut\b", 3),
+                    (r"(?m)^\s*impl\b", 3),
+                    (r"\bpub(\(crate\))?\s+(fn|struct|enum|mod|trait|use)\b", 3),
+                    (r"&(mut\s+)?self\b", 3),
+                    (r"\b(println|eprintln|format|vec|macro_rules)!", 3),
+                    (r"(?m)^\s*use\s+[\w:]+(::\{|::\*|;)", 2),
+                    (r"#\[(derive|cfg|test|allow)", 3),
+                    (r"\b(Option|Result|Vec|Box)<", 2),
+                    (r"\bmatch\s+\w+.*\{", 1),
+                    (r"\w::\w", 1),
+                ],
+            ),
+            build(
+                "typescript",
+                &[
+                    (r"(?m)^\s*import\s+.*\s+from\s+['\x22]", 3),
+                    (r"(?m)^\s*export\s+(default|const|function|interface|type|class|async)\b", 3),
+                    (r"\binterface\s+\w+\s*(<[^>]*>)?\s*\{", 3),
+                    (r":\s*(string|number|boolean|any|void|unknown|never)\b", 3),
+                    (r"(?m)^\s*type\s+\w+\s*=", 2),
+                    (r"\b(const|let)\s+\w+\s*(:\s*[\w<>\[\]]+)?\s*=", 1),
+                    (r"=>", 1),
+                    (r"===|!==", 2),
+                    (r"\bconsole\.\w+\(", 2),
+                    (r"\bfunction\s*\w*\s*\(", 2),
+                    (r"\b(async|await)\b", 1),
+                ],
+            ),
+            build(
+                "python",
+                &[
+                    (r"(?m)^\s*def\s+\w+\s*\(.*\)\s*(->\s*.+)?:\s*$", 4),
+                    (r"(?m)^\s*class\s+\w+(\(.*\))?:\s*$", 3),
+                    (r"(?m)^\s*from\s+[\w.]+\s+import\s+", 3),
+                    (r"(?m)^\s*import\s+[\w.]+(\s+as\s+\w+)?\s*$", 2),
+                    (r"(?m)^\s*(if|elif|for|while|with|try|except).*:\s*$", 2),
+                    (r"\bself\.\w+", 2),
+                    (r"\belif\b", 3),
+                    (r"__\w+__", 2),
+                    (r"\b(None|True|False)\b", 1),
+                    (r"\bprint\(", 1),
+                ],
+            ),
+            build(
+                "sql",
+                &[
+                    // 开头的关键字在英文句子里也很常见（Select the file...），只给 1 分，
+                    // 要靠后面的语句结构凑够分数
+                    (
+                        r"(?i)^\s*(select|insert\s+into|update|delete\s+from|create\s+(table|index|view|unique)|alter\s+table|drop\s+(table|index|view)|with\s+\w+\s+as)\b",
+                        1,
+                    ),
+                    (r"(?i)\bselect\s+(distinct\s+)?(\*|[\w.`\x22]+(\s*,\s*[\w.`\x22()*]+)*)\s+from\s+[\w.`\x22]+", 2),
+                    (
+                        r"(?i)\bfrom\s+[\w.`\x22]+(\s+(as\s+)?\w+)?\s+where\s+[\w.`\x22]+\s*(=|<>|!=|<=|>=|<|>|\bin\b|\blike\b|\bis\b|\bbetween\b)",
+                        4,
+                    ),
+                    (r"(?i)\binsert\s+into\s+[\w.`\x22]+\s*(\([^)]*\)\s*)?values\s*\(", 4),
+                    (r"(?i)\bupdate\s+[\w.`\x22]+\s+set\s+[\w.`\x22]+\s*=", 4),
+                    (r"(?i)\bcreate\s+(temporary\s+)?table\s+(if\s+not\s+exists\s+)?[\w.`\x22]+\s*\(", 4),
+                    (r"(?i)\b(alter|drop)\s+(table|index|view)\s+(if\s+exists\s+)?[\w.`\x22]+\s*(;|$|\b(add|drop|rename|alter)\b)", 3),
+                    (r";\s*$", 2),
+                    (r"(?i)\bwith\s+\w+\s+as\s*\(\s*select\b", 4),
+                    (
+                        r"(?i)\b((inner|left|right|full|cross)\s+(outer\s+)?join|join\s+[\w.`\x22]+(\s+(as\s+)?\w+)?\s+(on\s+[\w.`\x22]+\s*=|using\s*\())",
+                        2,
+                    ),
+                    (r"(?i)\b(group|order)\s+by\s+[\w.`\x22]+|\blimit\s+\d+", 2),
+                    (r"(?i)\b(primary\s+key|not\s+null|varchar|integer)\b", 2),
+                ],
+            ),
+            build(
+                "shell",
+                &[
+                    (r"^#!.*\b(sh|bash|zsh|fish)\b", 5),
+                    (r"(?m)^\s*\$\s+\w+", 2),
+                    (
+                        r"(?m)^\s*(sudo|apt|apt-get|brew|npm|pnpm|yarn|npx|cargo|git|docker|kubectl|curl|wget|chmod|chown|export|echo|cd|ls|grep|mkdir|rm|cp|mv|cat|tar|ssh|scp|systemctl|pip)\s",
+                        3,
+                    ),
+                    (r"\|\s*(grep|awk|sed|xargs|sort|uniq|head|tail|wc|jq|tee)\b", 3),
+                    (r"\|\s*(sudo\s+)?(ba|z)?sh\b", 3),
+                    (r"\s&&\s|\s\|\|\s", 1),
+                    (r"\$\{\w+\}|\$\(\w", 2),
+                    (r"(?m);\s*(then|do)\s*$", 3),
+                    (r"(?m)^\s*(fi|done|esac)\s*$", 3),
+                    (r"\s--[a-z][\w-]+", 1),
+                    (r"\s-[a-zA-Z]{1,3}\b", 1),
+                ],
+            ),
+            build(
+                "go",
+                &[
+                    (r"(?m)^\s*package\s+\w+\s*$", 4),
+                    (r"\bfunc\s+(\(\w+\s+\*?\w+\)\s*)?\w+\s*\(", 4),
+                    (r":=", 2),
+                    (r"\bfmt\.\w+\(", 3),
+                    (r"\berr\s*!=\s*nil\b", 4),
+                    (r"(?m)^\s*import\s*\(", 3),
+                    (r"\bgo\s+func\b|\bchan\s+\w+|\bdefer\s+\w+", 2),
+                    (r"\[\]\w+\{", 2),
+                ],
+            ),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_each_language() {
+        let cases = [
+            ("json", r#"{"name": "paste", "tags": [1, 2]}"#),
+            ("html", "<div class=\"a\">\n  <p>hi</p>\n</div>"),
+            (
+                "rust",
+                "pub fn parse(text: &str) -> Option<String> {\n    let mut out = String::new();\n    Some(out)\n}",
+            ),
+            (
+                "typescript",
+                "import { invoke } from \"@tauri-apps/api/core\";\nexport const id: number = 1;",
+            ),
+            (
+                "python",
+                "def greet(name):\n    if name is None:\n        return\n    print(name)",
+            ),
+            ("sql", "SELECT id, name FROM users WHERE age > 18 ORDER BY name;"),
+            ("sql", "insert into logs (a, b) values (1, 2)"),
+            (
+                "sql",
+                "select u.id from users u join orders o on o.user_id = u.id",
+            ),
+            (
+                "sql",
+                "WITH recent AS (SELECT * FROM orders)\nSELECT id FROM recent LIMIT 10",
+            ),
+            ("sql", "CREATE TABLE items (\n  id INTEGER PRIMARY KEY\n)"),
+            ("shell", "#!/bin/bash\nset -e\necho \"done\""),
+            ("shell", "cat access.log | grep 404 | sort | uniq -c"),
+            (
+                "go",
+                "package main\n\nfunc main() {\n\tif err != nil {\n\t\tfmt.Println(err)\n\t}\n}",
+            ),
+            ("yaml", "server:\n  port: 8080\n  hosts:\n    - a\n    - b"),
+        ];
+        for (language, text) in cases {
+            assert_eq!(detect_language(text), Some(language), "{text}");
+        }
+    }
+
+    #[test]
+    fn prose_is_not_code() {
+        let cases = [
+            "Select the file from the folder where you saved it.",
+            "Select everything from the menu and join us on Friday.",
+            "Update the docs and set a reminder for tomorrow.",
+            "Note: bring the laptop\nTodo: call Bob",
+            "Meeting at 3pm; don't be late.",
+            "Let me know if you can make it, thanks!",
+            "hi",
+            "{not json}",
+        ];
+        for text in cases {
+            assert_eq!(detect_language(text), None, "{text}");
+        }
+    }
+}
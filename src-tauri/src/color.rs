@@ -0,0 +1,853 @@
+use serde::Deserialize;
+
+/// 归一化后的颜色，分量均为 0-255
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// 重新复制颜色时可选的表示法
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorNotation {
+    Hex,
+    Rgb,
+    Hsl,
+    Hwb,
+    Oklch,
+    Name,
+    /// Android 资源里的 #AARRGGBB
+    Android,
+    /// Kotlin / Compose 的 Color(0xAARRGGBB)
+    Kotlin,
+}
+
+impl Rgba {
+    /// 数据库中统一保存为 #rrggbbaa
+    pub fn to_canonical(self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    pub fn format(self, notation: ColorNotation) -> String {
+        let alpha = self.a as f64 / 255.0;
+        let opaque = self.a == 255;
+        match notation {
+            ColorNotation::Hex => {
+                if opaque {
+                    format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+                } else {
+                    self.to_canonical()
+                }
+            }
+            ColorNotation::Rgb => {
+                if opaque {
+                    format!("rgb({}, {}, {})", self.r, self.g, self.b)
+                } else {
+                    format!(
+                        "rgba({}, {}, {}, {})",
+                        self.r,
+                        self.g,
+                        self.b,
+                        trim_number(alpha, 3)
+                    )
+                }
+            }
+            ColorNotation::Hsl => {
+                let (h, s, l) = self.to_hsl();
+                let body = format!(
+                    "{}, {}%, {}%",
+                    trim_number(h, 1),
+                    trim_number(s * 100.0, 1),
+                    trim_number(l * 100.0, 1)
+                );
+                if opaque {
+                    format!("hsl({body})")
+                } else {
+                    format!("hsla({body}, {})", trim_number(alpha, 3))
+                }
+            }
+            ColorNotation::Hwb => {
+                let (h, w, b) = self.to_hwb();
+                format!(
+                    "hwb({} {}% {}%{})",
+                    trim_number(h, 1),
+                    trim_number(w * 100.0, 1),
+                    trim_number(b * 100.0, 1),
+                    alpha_suffix(alpha, opaque)
+                )
+            }
+            ColorNotation::Oklch => {
+                let (l, c, h) = self.to_oklch();
+                format!(
+                    "oklch({}% {} {}{})",
+                    trim_number(l * 100.0, 2),
+                    trim_number(c, 4),
+                    trim_number(h, 2),
+                    alpha_suffix(alpha, opaque)
+                )
+            }
+            ColorNotation::Name => {
+                if self.a == 0 {
+                    return "transparent".to_string();
+                }
+                NAMED_COLORS
+                    .iter()
+                    .find(|(_, value)| {
+                        opaque && *value == u32::from_be_bytes([0, self.r, self.g, self.b])
+                    })
+                    .map(|(name, _)| name.to_string())
+                    .unwrap_or_else(|| self.format(ColorNotation::Hex))
+            }
+            ColorNotation::Android => {
+                format!("#{:02X}{:02X}{:02X}{:02X}", self.a, self.r, self.g, self.b)
+            }
+            ColorNotation::Kotlin => format!(
+                "Color(0x{:02X}{:02X}{:02X}{:02X})",
+                self.a, self.r, self.g, self.b
+            ),
+        }
+    }
+
+    fn channels(self) -> (f64, f64, f64) {
+        (
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0,
+        )
+    }
+
+    fn hue(self) -> f64 {
+        let (r, g, b) = self.channels();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        if delta == 0.0 {
+            return 0.0;
+        }
+        let hue = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        hue * 60.0
+    }
+
+    fn to_hsl(self) -> (f64, f64, f64) {
+        let (r, g, b) = self.channels();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let s = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        (self.hue(), s, l)
+    }
+
+    fn to_hwb(self) -> (f64, f64, f64) {
+        let (r, g, b) = self.channels();
+        (self.hue(), r.min(g).min(b), 1.0 - r.max(g).max(b))
+    }
+
+    fn to_oklch(self) -> (f64, f64, f64) {
+        let (r, g, b) = self.channels();
+        let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        let lightness = 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s;
+        let a = 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s;
+        let b = 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s;
+
+        let chroma = (a * a + b * b).sqrt();
+        let hue = if chroma < 1e-4 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        (lightness, chroma, hue)
+    }
+}
+
+/// 识别各种写法的颜色并归一化；source_app 用来判断 #AARRGGBB 这类有歧义的写法
+pub fn parse_color(text: &str, source_app: Option<&str>) -> Option<Rgba> {
+    let trimmed = text.trim().trim_end_matches(';').trim();
+    if trimmed.is_empty() || trimmed.len() > 64 {
+        return None;
+    }
+    let lower = trimmed.to_ascii_lowercase();
+
+    // Android 资源里的颜色是 #AARRGGBB，CSS 是 #RRGGBBAA
+    if let Some(value) = android_resource_value(&lower) {
+        return parse_android_hex(value.strip_prefix('#')?);
+    }
+    // 单独的单词（red、tan、linen）多半是普通文本，只在 CSS 声明里认命名颜色
+    if let Some(value) = css_declaration_value(&lower) {
+        return parse_value(value, true);
+    }
+    if let Some(hex) = lower.strip_prefix('#') {
+        if is_android_studio(source_app) {
+            return parse_android_hex(hex);
+        }
+    }
+    parse_value(&lower, false)
+}
+
+fn parse_value(lower: &str, allow_names: bool) -> Option<Rgba> {
+    if let Some(hex) = lower.strip_prefix('#') {
+        return parse_css_hex(hex);
+    }
+    if let Some(hex) = lower.strip_prefix("0x") {
+        return parse_argb_hex(hex);
+    }
+    // Kotlin / Compose: Color(0xFF2F80ED)
+    if let Some(inner) = lower
+        .strip_prefix("color(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return parse_argb_hex(inner.trim().strip_prefix("0x")?);
+    }
+    // Android: Color.parseColor("#AARRGGBB")
+    if let Some(inner) = lower
+        .strip_prefix("color.parsecolor(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let hex = inner.trim().trim_matches('"').strip_prefix('#')?;
+        return parse_argb_hex(hex);
+    }
+
+    if let Some((name, rest)) = lower.split_once('(') {
+        let args = rest.strip_suffix(')')?;
+        return match name.trim() {
+            "rgb" | "rgba" => parse_rgb_function(args),
+            "hsl" | "hsla" => parse_hsl_function(args),
+            "hwb" => parse_hwb_function(args),
+            "oklch" => parse_oklch_function(args),
+            _ => None,
+        };
+    }
+
+    if !allow_names {
+        return None;
+    }
+    if lower == "transparent" {
+        return Some(Rgba {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        });
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == lower)
+        .map(|(_, value)| {
+            let [_, r, g, b] = value.to_be_bytes();
+            Rgba { r, g, b, a: 255 }
+        })
+}
+
+// <color name="primary">#FF2F80ED</color> 或 android:textColor="#FF2F80ED"
+fn android_resource_value(lower: &str) -> Option<&str> {
+    if let Some(rest) = lower.strip_prefix("<color") {
+        let (_, value) = rest.split_once('>')?;
+        return Some(value.strip_suffix("</color>")?.trim());
+    }
+    let (name, value) = lower.split_once('=')?;
+    if !name.trim().starts_with("android:") {
+        return None;
+    }
+    Some(value.trim().trim_matches('"').trim())
+}
+
+// color: red、background-color: #fff 等声明的值
+fn css_declaration_value(lower: &str) -> Option<&str> {
+    let (property, value) = lower.split_once(':')?;
+    let property = property.trim();
+    let is_color_property = property == "color"
+        || property.ends_with("-color")
+        || matches!(property, "background" | "fill" | "stroke");
+    is_color_property.then(|| value.trim_end_matches("!important").trim())
+}
+
+fn is_android_studio(source_app: Option<&str>) -> bool {
+    source_app.is_some_and(|app| {
+        matches!(
+            app.to_ascii_lowercase().as_str(),
+            "studio64.exe" | "studio.exe" | "studio" | "studio.sh"
+        )
+    })
+}
+
+// Android 支持 #RGB、#ARGB、#RRGGBB 和 #AARRGGBB
+fn parse_android_hex(hex: &str) -> Option<Rgba> {
+    match hex.len() {
+        3 | 4 => parse_argb_hex(&hex.chars().flat_map(|c| [c, c]).collect::<String>()),
+        _ => parse_argb_hex(hex),
+    }
+}
+
+fn parse_css_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Rgba {
+            r: digit(0)?,
+            g: digit(1)?,
+            b: digit(2)?,
+            a: 255,
+        }),
+        4 => Some(Rgba {
+            r: digit(0)?,
+            g: digit(1)?,
+            b: digit(2)?,
+            a: digit(3)?,
+        }),
+        6 => Some(Rgba {
+            r: pair(0)?,
+            g: pair(2)?,
+            b: pair(4)?,
+            a: 255,
+        }),
+        8 => Some(Rgba {
+            r: pair(0)?,
+            g: pair(2)?,
+            b: pair(4)?,
+            a: pair(6)?,
+        }),
+        _ => None,
+    }
+}
+
+// 0xRRGGBB 或 Android 风格的 AARRGGBB
+fn parse_argb_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        6 => Some(Rgba {
+            r: pair(0)?,
+            g: pair(2)?,
+            b: pair(4)?,
+            a: 255,
+        }),
+        8 => Some(Rgba {
+            a: pair(0)?,
+            r: pair(2)?,
+            g: pair(4)?,
+            b: pair(6)?,
+        }),
+        _ => None,
+    }
+}
+
+// 同时支持 rgb(1, 2, 3, 0.5) 和 rgb(1 2 3 / 50%) 两种语法
+fn split_args(args: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (main, slash_alpha) = match args.split_once('/') {
+        Some((main, alpha)) => (main, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let mut parts: Vec<&str> = main
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.len() == 4 && slash_alpha.is_none() {
+        let alpha = parts.pop();
+        return Some((parts, alpha));
+    }
+    if parts.len() != 3 {
+        return None;
+    }
+    Some((parts, slash_alpha))
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    if value == "none" {
+        return Some(0.0);
+    }
+    let number: f64 = value.parse().ok()?;
+    number.is_finite().then_some(number)
+}
+
+fn parse_percentage(value: &str) -> Option<f64> {
+    parse_number(value.strip_suffix('%')?).map(|v| v / 100.0)
+}
+
+fn parse_alpha(value: Option<&str>) -> Option<u8> {
+    let Some(value) = value else {
+        return Some(255);
+    };
+    let alpha = match value.strip_suffix('%') {
+        Some(percent) => parse_number(percent)? / 100.0,
+        None => parse_number(value)?,
+    };
+    Some(unit_to_byte(alpha))
+}
+
+fn parse_hue(value: &str) -> Option<f64> {
+    let degrees = if let Some(v) = value.strip_suffix("deg") {
+        parse_number(v)?
+    } else if let Some(v) = value.strip_suffix("grad") {
+        parse_number(v)? * 0.9
+    } else if let Some(v) = value.strip_suffix("rad") {
+        parse_number(v)?.to_degrees()
+    } else if let Some(v) = value.strip_suffix("turn") {
+        parse_number(v)? * 360.0
+    } else {
+        parse_number(value)?
+    };
+    Some(degrees.rem_euclid(360.0))
+}
+
+fn parse_rgb_function(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let channel = |value: &str| -> Option<u8> {
+        match value.strip_suffix('%') {
+            Some(percent) => Some(unit_to_byte(parse_number(percent)? / 100.0)),
+            None => Some(parse_number(value)?.round().clamp(0.0, 255.0) as u8),
+        }
+    };
+    Some(Rgba {
+        r: channel(parts[0])?,
+        g: channel(parts[1])?,
+        b: channel(parts[2])?,
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn parse_hsl_function(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let h = parse_hue(parts[0])?;
+    let s = parse_percentage(parts[1]).or_else(|| parse_number(parts[1]).map(|v| v / 100.0))?;
+    let l = parse_percentage(parts[2]).or_else(|| parse_number(parts[2]).map(|v| v / 100.0))?;
+    let (r, g, b) = hsl_to_rgb(h, s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+    Some(Rgba {
+        r: unit_to_byte(r),
+        g: unit_to_byte(g),
+        b: unit_to_byte(b),
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn parse_hwb_function(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let h = parse_hue(parts[0])?;
+    let mut w = parse_percentage(parts[1])?.clamp(0.0, 1.0);
+    let mut b = parse_percentage(parts[2])?.clamp(0.0, 1.0);
+    if w + b >= 1.0 {
+        let gray = w / (w + b);
+        w = gray;
+        b = 1.0 - gray;
+    }
+    let (r, g, bl) = hsl_to_rgb(h, 1.0, 0.5);
+    let scale = |c: f64| c * (1.0 - w - b) + w;
+    Some(Rgba {
+        r: unit_to_byte(scale(r)),
+        g: unit_to_byte(scale(g)),
+        b: unit_to_byte(scale(bl)),
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn parse_oklch_function(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let lightness = parse_percentage(parts[0]).or_else(|| parse_number(parts[0]))?;
+    // 规范里 100% 的 chroma 对应 0.4
+    let chroma = match parts[1].strip_suffix('%') {
+        Some(percent) => parse_number(percent)? / 100.0 * 0.4,
+        None => parse_number(parts[1])?,
+    };
+    let hue = parse_hue(parts[2])?.to_radians();
+
+    let a = chroma * hue.cos();
+    let b = chroma * hue.sin();
+    let l_ = lightness + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = lightness - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = lightness - 0.0894841775 * a - 1.2914855480 * b;
+    let (l, m, s) = (l_.powi(3), m_.powi(3), s_.powi(3));
+
+    let r = 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s;
+    let g = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s;
+    let bl = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s;
+    Some(Rgba {
+        r: unit_to_byte(linear_to_srgb(r)),
+        g: unit_to_byte(linear_to_srgb(g)),
+        b: unit_to_byte(linear_to_srgb(bl)),
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let sector = h / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = l - chroma / 2.0;
+    (r + m, g + m, b + m)
+}
+
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn unit_to_byte(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn alpha_suffix(alpha: f64, opaque: bool) -> String {
+    if opaque {
+        String::new()
+    } else {
+        format!(" / {}", trim_number(alpha, 3))
+    }
+}
+
+fn trim_number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{value:.decimals$}");
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+// CSS Color Module Level 4 命名颜色
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+
+    fn close(left: Rgba, right: Rgba) -> bool {
+        let near = |x: u8, y: u8| x.abs_diff(y) <= 1;
+        near(left.r, right.r) && near(left.g, right.g) && near(left.b, right.b) && left.a == right.a
+    }
+
+    #[test]
+    fn parses_each_notation() {
+        let cases = [
+            ("#2f80ed", rgba(0x2f, 0x80, 0xed, 255)),
+            ("#abc", rgba(0xaa, 0xbb, 0xcc, 255)),
+            ("#abcd", rgba(0xaa, 0xbb, 0xcc, 0xdd)),
+            ("#2F80ED80;", rgba(0x2f, 0x80, 0xed, 0x80)),
+            ("0x2f80ed", rgba(0x2f, 0x80, 0xed, 255)),
+            ("rgb(47, 128, 237)", rgba(47, 128, 237, 255)),
+            ("rgba(47, 128, 237, 0.5)", rgba(47, 128, 237, 128)),
+            ("rgb(47 128 237 / 50%)", rgba(47, 128, 237, 128)),
+            ("rgb(100% 0% 0%)", rgba(255, 0, 0, 255)),
+            ("hsl(0, 100%, 50%)", rgba(255, 0, 0, 255)),
+            ("hsl(120deg 100% 25% / 0.5)", rgba(0, 128, 0, 128)),
+            ("hwb(240 0% 0%)", rgba(0, 0, 255, 255)),
+            ("oklch(62.8% 0.2577 29.23)", rgba(255, 0, 0, 255)),
+            ("Color(0xFF2F80ED)", rgba(0x2f, 0x80, 0xed, 255)),
+            (
+                "Color.parseColor(\"#802F80ED\")",
+                rgba(0x2f, 0x80, 0xed, 0x80),
+            ),
+        ];
+        for (text, expected) in cases {
+            let parsed = parse_color(text, None).unwrap_or_else(|| panic!("{text}"));
+            assert!(close(parsed, expected), "{text}: {parsed:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_non_colors() {
+        for text in [
+            "#12345",
+            "#ggg",
+            "rgb(1, 2)",
+            "hsl(a, b, c)",
+            "foo(1, 2, 3)",
+            "0x123",
+            "",
+        ] {
+            assert_eq!(parse_color(text, None), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn eight_digit_hex_is_rrggbbaa_without_android_context() {
+        assert_eq!(parse_color("#80FF0000", None), Some(rgba(0x80, 0xff, 0, 0)));
+        assert_eq!(
+            parse_color("#80FF0000", Some("Code.exe")),
+            Some(rgba(0x80, 0xff, 0, 0))
+        );
+    }
+
+    #[test]
+    fn android_resources_and_studio_read_argb() {
+        let red = Some(rgba(0xff, 0, 0, 0x80));
+        assert_eq!(parse_color("#80FF0000", Some("studio64.exe")), red);
+        assert_eq!(parse_color("#80FF0000", Some("studio.sh")), red);
+        assert_eq!(
+            parse_color(r#"<color name="primary">#80FF0000</color>"#, None),
+            red
+        );
+        assert_eq!(parse_color(r##"android:textColor="#80FF0000""##, None), red);
+        // #ARGB 简写
+        assert_eq!(
+            parse_color(r#"<color name="a">#8F00</color>"#, None),
+            Some(rgba(0xff, 0, 0, 0x88))
+        );
+    }
+
+    #[test]
+    fn named_colors_only_in_css_declarations() {
+        for word in ["red", "tan", "linen", "transparent"] {
+            assert_eq!(parse_color(word, None), None, "{word}");
+        }
+        assert_eq!(parse_color("color: red;", None), Some(rgba(255, 0, 0, 255)));
+        assert_eq!(
+            parse_color("background-color: Tan !important", None),
+            Some(rgba(0xd2, 0xb4, 0x8c, 255))
+        );
+        assert_eq!(
+            parse_color("fill: transparent", None),
+            Some(rgba(0, 0, 0, 0))
+        );
+        assert_eq!(parse_color("font: red", None), None);
+        assert_eq!(parse_color("color: notacolor", None), None);
+    }
+
+    #[test]
+    fn formats_each_notation() {
+        let color = rgba(0x2f, 0x80, 0xed, 255);
+        let translucent = rgba(255, 0, 0, 128);
+        let cases = [
+            (color, ColorNotation::Hex, "#2f80ed"),
+            (translucent, ColorNotation::Hex, "#ff000080"),
+            (color, ColorNotation::Rgb, "rgb(47, 128, 237)"),
+            (translucent, ColorNotation::Rgb, "rgba(255, 0, 0, 0.502)"),
+            (translucent, ColorNotation::Hsl, "hsla(0, 100%, 50%, 0.502)"),
+            (rgba(0, 0, 255, 255), ColorNotation::Hwb, "hwb(240 0% 0%)"),
+            (rgba(255, 0, 0, 255), ColorNotation::Name, "red"),
+            (color, ColorNotation::Name, "#2f80ed"),
+            (rgba(1, 2, 3, 0), ColorNotation::Name, "transparent"),
+            (translucent, ColorNotation::Android, "#80FF0000"),
+            (color, ColorNotation::Kotlin, "Color(0xFF2F80ED)"),
+        ];
+        for (color, notation, expected) in cases {
+            assert_eq!(color.format(notation), expected, "{notation:?}");
+        }
+        assert_eq!(translucent.to_canonical(), "#ff000080");
+    }
+
+    #[test]
+    fn round_trips_through_each_notation() {
+        let colors = [
+            rgba(0x2f, 0x80, 0xed, 255),
+            rgba(255, 0, 0, 128),
+            rgba(12, 200, 99, 255),
+            rgba(128, 128, 128, 51),
+        ];
+        for color in colors {
+            for notation in [
+                ColorNotation::Hex,
+                ColorNotation::Rgb,
+                ColorNotation::Hsl,
+                ColorNotation::Hwb,
+                ColorNotation::Oklch,
+                ColorNotation::Kotlin,
+            ] {
+                let text = color.format(notation);
+                let parsed = parse_color(&text, None).unwrap_or_else(|| panic!("{text}"));
+                assert!(close(parsed, color), "{text}: {parsed:?}");
+            }
+            let android = color.format(ColorNotation::Android);
+            assert_eq!(parse_color(&android, Some("studio64.exe")), Some(color));
+        }
+        let named = rgba(0xfa, 0xf0, 0xe6, 255).format(ColorNotation::Name);
+        assert_eq!(
+            parse_color(&format!("color: {named}"), None),
+            Some(rgba(0xfa, 0xf0, 0xe6, 255))
+        );
+    }
+}
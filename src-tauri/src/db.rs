@@ -1,8 +1,22 @@
 use std::path::Path;
 
-use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+use sqlx::{sqlite::SqliteConnectOptions, Row, SqlitePool};
 
-use crate::models::{ClipboardItemRow, NewClipboardItem};
+use crate::entities;
+use crate::models::{
+    ClipboardCategory, ClipboardFormat, ClipboardItemRow, Entity, EntityKind, EntityKindCount,
+    LanguageCount, LinkHostCount, NewClipboardItem, NewEntity, NewPathEntry, PathEntry, PathKind,
+    SnippetInput, SnippetRow,
+};
+
+// PRAGMA user_version 记录已经执行过的迁移
+const TYPED_COLUMNS_VERSION: i64 = 1;
+const ENTITIES_VERSION: i64 = 2;
+const OCR_INDEX_VERSION: i64 = 3;
+
+const ITEM_COLUMNS: &str = "id, format, category, text, html, file_path, color, link_url, link_host, link_scheme, language, data_format, data_valid, data_shape, source_app, custom_category, tags, metadata, ocr_text, image, image_width, image_height, created_at, last_used_at";
+
+const SNIPPET_COLUMNS: &str = "id, name, content, tags, created_at, updated_at, last_used_at";
 
 pub async fn init_db(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
     let options = SqliteConnectOptions::new()
@@ -35,14 +49,352 @@ pub async fn init_db(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
     .execute(&pool)
     .await?;
 
+    // 旧版本数据库缺少的列在这里补齐
+    ensure_column(&pool, "clipboard_items", "link_url", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "link_host", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "link_scheme", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "language", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "data_format", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "data_valid", "INTEGER").await?;
+    ensure_column(&pool, "clipboard_items", "data_shape", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "source_app", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "custom_category", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "tags", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "metadata", "TEXT").await?;
+    migrate_typed_columns(&pool).await?;
+    create_entities_table(&pool).await?;
+    create_paths_table(&pool).await?;
+    create_snippets_table(&pool).await?;
+    // 以下是重建表之后新增的列
+    ensure_column(&pool, "clipboard_items", "ocr_text", "TEXT").await?;
+    ensure_column(&pool, "clipboard_items", "ocr_status", "TEXT").await?;
+    create_ocr_index(&pool).await?;
+    ensure_column(&pool, "clipboard_items", "image_phash", "INTEGER").await?;
+    ensure_column(&pool, "clipboard_items", "last_used_at", "INTEGER").await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_items_link_host ON clipboard_items (link_host)",
+    )
+    .execute(&pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_items_language ON clipboard_items (language)",
+    )
+    .execute(&pool)
+    .await?;
+    // OCR 文本改由 clipboard_items_ocr 全文索引，旧版本建的 B-tree 索引用不上
+    sqlx::query("DROP INDEX IF EXISTS idx_clipboard_items_ocr_text")
+        .execute(&pool)
+        .await?;
+    // OCR 后台任务按这个索引查找待识别的图片
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_items_ocr_pending
+     ON clipboard_items (format, ocr_status)",
+    )
+    .execute(&pool)
+    .await?;
+
     Ok(pool)
 }
 
-pub async fn insert_item(pool: &SqlitePool, item: NewClipboardItem) -> Result<(), sqlx::Error> {
+// SQLite 不能给已有列追加 CHECK 约束，只能规范化旧数据后重建表
+async fn migrate_typed_columns(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    if version >= TYPED_COLUMNS_VERSION {
+        return Ok(());
+    }
+
+    let formats = sql_list(ClipboardFormat::ALL.map(ClipboardFormat::as_str));
+    let categories = sql_list(ClipboardCategory::ALL.map(ClipboardCategory::as_str));
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE clipboard_items
+     SET format = lower(trim(format)), category = lower(trim(category))",
+    )
+    .execute(&mut *tx)
+    .await?;
+    // 无法识别的值按纯文本处理，图片记录以格式为准
+    sqlx::query(&format!(
+        "UPDATE clipboard_items SET format = 'text' WHERE format NOT IN ({formats})"
+    ))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "UPDATE clipboard_items
+     SET category = CASE WHEN format = 'image' THEN 'image' ELSE 'text' END
+     WHERE category NOT IN ({categories})"
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(&format!(
+        "CREATE TABLE clipboard_items_new (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      format TEXT NOT NULL CHECK (format IN ({formats})),
+      category TEXT NOT NULL CHECK (category IN ({categories})),
+      text TEXT,
+      html TEXT,
+      file_path TEXT,
+      color TEXT,
+      link_url TEXT,
+      link_host TEXT,
+      link_scheme TEXT,
+      language TEXT,
+      data_format TEXT,
+      data_valid INTEGER,
+      data_shape TEXT,
+      source_app TEXT,
+      custom_category TEXT,
+      tags TEXT,
+      metadata TEXT,
+      image BLOB,
+      image_width INTEGER,
+      image_height INTEGER,
+      created_at INTEGER NOT NULL
+    )"
+    ))
+    .execute(&mut *tx)
+    .await?;
+    // 重建时的列固定为这次迁移时的表结构，之后新增的列在迁移完成后再补
+    let columns = "id, format, category, text, html, file_path, color, link_url, link_host, link_scheme, language, data_format, data_valid, data_shape, source_app, custom_category, tags, metadata, image, image_width, image_height, created_at";
+    sqlx::query(&format!(
+        "INSERT INTO clipboard_items_new ({columns})
+     SELECT {columns} FROM clipboard_items"
+    ))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DROP TABLE clipboard_items")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("ALTER TABLE clipboard_items_new RENAME TO clipboard_items")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!("PRAGMA user_version = {TYPED_COLUMNS_VERSION}"))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn create_entities_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let kinds = sql_list(EntityKind::ALL.map(EntityKind::as_str));
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS item_entities (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      item_id INTEGER NOT NULL REFERENCES clipboard_items (id) ON DELETE CASCADE,
+      kind TEXT NOT NULL CHECK (kind IN ({kinds})),
+      value TEXT NOT NULL,
+      normalized TEXT NOT NULL
+    )"
+    ))
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_item_entities_kind ON item_entities (kind, normalized)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_item_entities_item ON item_entities (item_id)")
+        .execute(pool)
+        .await?;
+
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    if version >= ENTITIES_VERSION {
+        return Ok(());
+    }
+    // 升级前的历史记录补提取一次实体
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, text FROM clipboard_items WHERE text IS NOT NULL")
+            .fetch_all(pool)
+            .await?;
+    let mut tx = pool.begin().await?;
+    for (id, text) in rows {
+        insert_entities(&mut tx, id, &entities::extract_entities(&text)).await?;
+    }
+    sqlx::query(&format!("PRAGMA user_version = {ENTITIES_VERSION}"))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+// OCR 文本可能很长，用 trigram 分词的 FTS5 外部内容表索引，由触发器和 clipboard_items 保持同步。
+// trigram 分词时 LIKE '%q%' 可以直接走索引，中英文都不需要额外分词
+async fn create_ocr_index(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_items_ocr USING fts5(
+      ocr_text, content = 'clipboard_items', content_rowid = 'id', tokenize = 'trigram'
+    )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clipboard_items_ocr_insert
+     AFTER INSERT ON clipboard_items WHEN new.ocr_text IS NOT NULL BEGIN
+       INSERT INTO clipboard_items_ocr (rowid, ocr_text) VALUES (new.id, new.ocr_text);
+     END",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clipboard_items_ocr_delete
+     AFTER DELETE ON clipboard_items WHEN old.ocr_text IS NOT NULL BEGIN
+       INSERT INTO clipboard_items_ocr (clipboard_items_ocr, rowid, ocr_text)
+       VALUES ('delete', old.id, old.ocr_text);
+     END",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clipboard_items_ocr_update
+     AFTER UPDATE OF ocr_text ON clipboard_items BEGIN
+       INSERT INTO clipboard_items_ocr (clipboard_items_ocr, rowid, ocr_text)
+       SELECT 'delete', old.id, old.ocr_text WHERE old.ocr_text IS NOT NULL;
+       INSERT INTO clipboard_items_ocr (rowid, ocr_text)
+       SELECT new.id, new.ocr_text WHERE new.ocr_text IS NOT NULL;
+     END",
+    )
+    .execute(pool)
+    .await?;
+
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    if version >= OCR_INDEX_VERSION {
+        return Ok(());
+    }
+    // 升级前已经识别过的图片重建一次索引
+    sqlx::query("INSERT INTO clipboard_items_ocr (clipboard_items_ocr) VALUES ('rebuild')")
+        .execute(pool)
+        .await?;
+    sqlx::query(&format!("PRAGMA user_version = {OCR_INDEX_VERSION}"))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn create_paths_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let kinds = sql_list(PathKind::ALL.map(PathKind::as_str));
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS item_paths (
+      item_id INTEGER NOT NULL REFERENCES clipboard_items (id) ON DELETE CASCADE,
+      position INTEGER NOT NULL,
+      path TEXT NOT NULL,
+      expanded TEXT NOT NULL,
+      path_exists INTEGER,
+      kind TEXT CHECK (kind IN ({kinds})),
+      size INTEGER,
+      checked_at INTEGER,
+      PRIMARY KEY (item_id, position)
+    )"
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_snippets_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     sqlx::query(
+        "CREATE TABLE IF NOT EXISTS snippets (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL,
+      content TEXT NOT NULL,
+      tags TEXT,
+      created_at INTEGER NOT NULL,
+      updated_at INTEGER NOT NULL,
+      last_used_at INTEGER
+    )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn insert_entities(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    item_id: i64,
+    entities: &[NewEntity],
+) -> Result<(), sqlx::Error> {
+    for entity in entities {
+        sqlx::query(
+            "INSERT INTO item_entities (item_id, kind, value, normalized) VALUES (?, ?, ?, ?)",
+        )
+        .bind(item_id)
+        .bind(entity.kind)
+        .bind(&entity.value)
+        .bind(&entity.normalized)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn insert_paths(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    item_id: i64,
+    paths: &[NewPathEntry],
+) -> Result<(), sqlx::Error> {
+    for (position, entry) in paths.iter().enumerate() {
+        sqlx::query(
+            "INSERT OR REPLACE INTO item_paths (
+        item_id, position, path, expanded, path_exists, kind, size, checked_at
+      ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(item_id)
+        .bind(position as i64)
+        .bind(&entry.path)
+        .bind(&entry.expanded)
+        .bind(entry.exists)
+        .bind(entry.kind)
+        .bind(entry.size)
+        .bind(entry.checked_at)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+fn sql_list<const N: usize>(values: [&str; N]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+async fn ensure_column(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({table})"))
+        .fetch_all(pool)
+        .await?;
+    let exists = rows
+        .iter()
+        .any(|row| row.get::<String, _>("name") == column);
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn insert_item(pool: &SqlitePool, item: NewClipboardItem) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let id = sqlx::query(
         "INSERT INTO clipboard_items (
-        format, category, text, html, file_path, color, image, image_width, image_height, created_at
-      ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        format, category, text, html, file_path, color, link_url, link_host, link_scheme,
+        language, data_format, data_valid, data_shape, source_app, custom_category, tags,
+        metadata, image_phash, image, image_width, image_height, created_at
+      ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(item.format)
     .bind(item.category)
@@ -50,29 +402,45 @@ pub async fn insert_item(pool: &SqlitePool, item: NewClipboardItem) -> Result<()
     .bind(item.html)
     .bind(item.file_path)
     .bind(item.color)
+    .bind(item.link_url)
+    .bind(item.link_host)
+    .bind(item.link_scheme)
+    .bind(item.language)
+    .bind(item.data_format)
+    .bind(item.data_valid)
+    .bind(item.data_shape)
+    .bind(item.source_app)
+    .bind(item.custom_category)
+    .bind(item.tags)
+    .bind(item.metadata)
+    .bind(item.image_phash)
     .bind(item.image)
     .bind(item.image_width)
     .bind(item.image_height)
     .bind(item.created_at)
-    .execute(pool)
-    .await?;
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+    insert_entities(&mut tx, id, &item.entities).await?;
+    insert_paths(&mut tx, id, &item.paths).await?;
+    tx.commit().await?;
 
-    Ok(())
+    Ok(id)
 }
 
 pub async fn list_items(
     pool: &SqlitePool,
     limit: i64,
 ) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
      ORDER BY created_at DESC
      LIMIT ?",
-  )
-  .bind(limit)
-  .fetch_all(pool)
-  .await?;
+    ))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows)
 }
@@ -83,33 +451,46 @@ pub async fn search_items(
     limit: i64,
 ) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
     let pattern = format!("%{}%", query);
-    let rows = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
-     WHERE text LIKE ? OR html LIKE ? OR file_path LIKE ? OR color LIKE ?
+     WHERE text LIKE ? OR html LIKE ? OR file_path LIKE ? OR color LIKE ? OR link_host LIKE ?
+       OR {}
      ORDER BY created_at DESC
      LIMIT ?",
-  )
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(limit)
-  .fetch_all(pool)
-  .await?;
+        ocr_condition(query),
+    ))
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows)
 }
 
+// trigram 索引只对至少三个字符的查询生效，更短的查询直接扫描 ocr_text
+fn ocr_condition(query: &str) -> &'static str {
+    if query.chars().count() >= 3 {
+        "id IN (SELECT rowid FROM clipboard_items_ocr WHERE ocr_text LIKE ?)"
+    } else {
+        "ocr_text LIKE ?"
+    }
+}
+
 pub async fn get_item(pool: &SqlitePool, id: i64) -> Result<Option<ClipboardItemRow>, sqlx::Error> {
-    let row = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let row = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
      WHERE id = ?",
-  )
-  .bind(id)
-  .fetch_optional(pool)
-  .await?;
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
 
     Ok(row)
 }
@@ -120,18 +501,38 @@ pub async fn list_items_by_date_range(
     end_ts: i64,
     limit: i64,
 ) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
      WHERE created_at >= ? AND created_at <= ?
      ORDER BY created_at DESC
      LIMIT ?",
-  )
-  .bind(start_ts)
-  .bind(end_ts)
-  .bind(limit)
-  .fetch_all(pool)
-  .await?;
+    ))
+    .bind(start_ts)
+    .bind(end_ts)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// 时间范围内的图片 id，按时间先后排序，导出时逐条读取以免一次加载全部图片
+pub async fn list_image_ids_by_date_range(
+    pool: &SqlitePool,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query_scalar::<_, i64>(
+        "SELECT id
+     FROM clipboard_items
+     WHERE format = 'image' AND created_at >= ? AND created_at <= ?
+     ORDER BY created_at ASC, id ASC",
+    )
+    .bind(start_ts)
+    .bind(end_ts)
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows)
 }
@@ -144,41 +545,443 @@ pub async fn search_items_by_date_range(
     limit: i64,
 ) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
     let pattern = format!("%{}%", query);
-    let rows = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
-     WHERE (text LIKE ? OR html LIKE ? OR file_path LIKE ? OR color LIKE ?)
+     WHERE (text LIKE ? OR html LIKE ? OR file_path LIKE ? OR color LIKE ? OR link_host LIKE ?
+       OR {})
        AND created_at >= ? AND created_at <= ?
      ORDER BY created_at DESC
      LIMIT ?",
-  )
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(&pattern)
-  .bind(start_ts)
-  .bind(end_ts)
-  .bind(limit)
-  .fetch_all(pool)
-  .await?;
+        ocr_condition(query),
+    ))
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(start_ts)
+    .bind(end_ts)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows)
 }
 
 pub async fn get_latest_item(pool: &SqlitePool) -> Result<Option<ClipboardItemRow>, sqlx::Error> {
-    let row = sqlx::query_as::<_, ClipboardItemRow>(
-    "SELECT id, format, category, text, html, file_path, color, image, image_width, image_height, created_at
+    let row = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
      FROM clipboard_items
      ORDER BY created_at DESC
      LIMIT 1",
-  )
-  .fetch_optional(pool)
-  .await?;
+    ))
+    .fetch_optional(pool)
+    .await?;
 
     Ok(row)
 }
 
+pub async fn list_link_hosts(pool: &SqlitePool) -> Result<Vec<LinkHostCount>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, LinkHostCount>(
+        "SELECT link_host AS host, COUNT(*) AS count
+     FROM clipboard_items
+     WHERE link_host IS NOT NULL
+     GROUP BY link_host
+     ORDER BY count DESC, host ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_items_by_link_host(
+    pool: &SqlitePool,
+    host: &str,
+    limit: i64,
+) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
+    // 旧版本保存的是 punycode，Unicode 和 punycode 两种写法都匹配
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
+     FROM clipboard_items
+     WHERE link_host IN (?, ?)
+     ORDER BY created_at DESC
+     LIMIT ?",
+    ))
+    .bind(url::quirks::domain_to_unicode(host))
+    .bind(url::quirks::domain_to_ascii(host))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_languages(pool: &SqlitePool) -> Result<Vec<LanguageCount>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, LanguageCount>(
+        "SELECT language, COUNT(*) AS count
+     FROM clipboard_items
+     WHERE language IS NOT NULL
+     GROUP BY language
+     ORDER BY count DESC, language ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_items_by_language(
+    pool: &SqlitePool,
+    language: &str,
+    limit: i64,
+) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
+     FROM clipboard_items
+     WHERE language = ?
+     ORDER BY created_at DESC
+     LIMIT ?",
+    ))
+    .bind(language)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_entity_kinds(pool: &SqlitePool) -> Result<Vec<EntityKindCount>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, EntityKindCount>(
+        "SELECT kind, COUNT(DISTINCT item_id) AS count
+     FROM item_entities
+     GROUP BY kind
+     ORDER BY count DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_item_entities(pool: &SqlitePool, id: i64) -> Result<Vec<Entity>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, Entity>(
+        "SELECT item_id, kind, value, normalized
+     FROM item_entities
+     WHERE item_id = ?
+     ORDER BY id ASC",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// 按实体筛选记录；kind 为空时匹配所有类型，query 为空时只按类型筛选
+pub async fn list_items_by_entity(
+    pool: &SqlitePool,
+    kind: Option<EntityKind>,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
+    let pattern = format!("%{}%", query.trim());
+    // 电话号码按数字匹配，输入 138-0013 也能找到 +8613800138000
+    let digits_pattern = entities::phone_digits(query)
+        .map(|digits| format!("%{digits}%"))
+        .unwrap_or_else(|| pattern.clone());
+    let rows = sqlx::query_as::<_, ClipboardItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS}
+     FROM clipboard_items
+     WHERE id IN (
+       SELECT item_id FROM item_entities
+       WHERE (? IS NULL OR kind = ?)
+         AND (value LIKE ? OR normalized LIKE ? OR (kind = 'phone' AND normalized LIKE ?))
+     )
+     ORDER BY created_at DESC
+     LIMIT ?",
+    ))
+    .bind(kind)
+    .bind(kind)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&digits_pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn list_item_paths(pool: &SqlitePool, id: i64) -> Result<Vec<PathEntry>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, PathEntry>(
+        "SELECT item_id, position, path, expanded, path_exists, kind, size, checked_at
+     FROM item_paths
+     WHERE item_id = ?
+     ORDER BY position ASC",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// 整体替换一条记录的路径状态，用于刷新和给旧记录补齐
+pub async fn save_item_paths(
+    pool: &SqlitePool,
+    id: i64,
+    paths: &[NewPathEntry],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM item_paths WHERE item_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    insert_paths(&mut tx, id, paths).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 取出一张还没有做过 OCR 的图片，最新的优先
+pub async fn next_ocr_pending(
+    pool: &SqlitePool,
+) -> Result<Option<(i64, Vec<u8>, i64, i64)>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (i64, Vec<u8>, i64, i64)>(
+        "SELECT id, image, image_width, image_height
+     FROM clipboard_items
+     WHERE format = 'image' AND ocr_status IS NULL
+       AND image IS NOT NULL AND image_width IS NOT NULL AND image_height IS NOT NULL
+     ORDER BY created_at DESC
+     LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn save_ocr_result(
+    pool: &SqlitePool,
+    id: i64,
+    text: Option<&str>,
+    status: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE clipboard_items SET ocr_text = ?, ocr_status = ? WHERE id = ?")
+        .bind(text)
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 记录从历史写回剪贴板的时间，不改变记录在列表中的位置
+pub async fn touch_item(pool: &SqlitePool, id: i64, used_at: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE clipboard_items SET last_used_at = ? WHERE id = ?")
+        .bind(used_at)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 最近图片的感知哈希，limit 为空时返回全部
+pub async fn list_image_hashes(
+    pool: &SqlitePool,
+    limit: Option<i64>,
+) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT id, image_phash
+     FROM clipboard_items
+     WHERE format = 'image' AND image_phash IS NOT NULL
+     ORDER BY created_at DESC
+     LIMIT ?",
+    )
+    .bind(limit.unwrap_or(-1))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn next_image_without_hash(
+    pool: &SqlitePool,
+    before_id: i64,
+) -> Result<Option<(i64, Vec<u8>, i64, i64)>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (i64, Vec<u8>, i64, i64)>(
+        "SELECT id, image, image_width, image_height
+     FROM clipboard_items
+     WHERE format = 'image' AND image_phash IS NULL AND id < ?
+       AND image IS NOT NULL AND image_width IS NOT NULL AND image_height IS NOT NULL
+     ORDER BY id DESC
+     LIMIT 1",
+    )
+    .bind(before_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn save_image_hash(pool: &SqlitePool, id: i64, hash: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE clipboard_items SET image_phash = ? WHERE id = ?")
+        .bind(hash)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 合并近似重复的截图：已有记录移到最前，新截图分辨率更高时替换图片并重新 OCR
+pub async fn merge_image_duplicate(
+    pool: &SqlitePool,
+    id: i64,
+    item: NewClipboardItem,
+) -> Result<(), sqlx::Error> {
+    let area = item.image_width.unwrap_or(0) * item.image_height.unwrap_or(0);
+    sqlx::query(
+        "UPDATE clipboard_items SET
+       image = CASE WHEN image_width * image_height < ? THEN ? ELSE image END,
+       image_phash = CASE WHEN image_width * image_height < ? THEN ? ELSE image_phash END,
+       ocr_text = CASE WHEN image_width * image_height < ? THEN NULL ELSE ocr_text END,
+       ocr_status = CASE WHEN image_width * image_height < ? THEN NULL ELSE ocr_status END,
+       image_height = CASE WHEN image_width * image_height < ? THEN ? ELSE image_height END,
+       image_width = CASE WHEN image_width * image_height < ? THEN ? ELSE image_width END,
+       created_at = ?
+     WHERE id = ?",
+    )
+    .bind(area)
+    .bind(item.image)
+    .bind(area)
+    .bind(item.image_phash)
+    .bind(area)
+    .bind(area)
+    .bind(area)
+    .bind(item.image_height)
+    .bind(area)
+    .bind(item.image_width)
+    .bind(item.created_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_items(
+    pool: &SqlitePool,
+    ids: &[i64],
+) -> Result<Vec<ClipboardItemRow>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!("SELECT {ITEM_COLUMNS} FROM clipboard_items WHERE id IN ({placeholders})");
+    let mut query = sqlx::query_as::<_, ClipboardItemRow>(&sql);
+    for id in ids {
+        query = query.bind(id);
+    }
+    query.fetch_all(pool).await
+}
+
+/// 按名称排序列出片段，query 不为空时匹配名称、内容和标签
+pub async fn list_snippets(
+    pool: &SqlitePool,
+    query: Option<&str>,
+) -> Result<Vec<SnippetRow>, sqlx::Error> {
+    let pattern = format!("%{}%", query.unwrap_or_default());
+    sqlx::query_as::<_, SnippetRow>(&format!(
+        "SELECT {SNIPPET_COLUMNS}
+     FROM snippets
+     WHERE name LIKE ? OR content LIKE ? OR tags LIKE ?
+     ORDER BY name COLLATE NOCASE",
+    ))
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_snippet(pool: &SqlitePool, id: i64) -> Result<Option<SnippetRow>, sqlx::Error> {
+    sqlx::query_as::<_, SnippetRow>(&format!(
+        "SELECT {SNIPPET_COLUMNS} FROM snippets WHERE id = ?",
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_snippet(
+    pool: &SqlitePool,
+    snippet: &SnippetInput,
+    created_at: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO snippets (name, content, tags, created_at, updated_at)
+     VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&snippet.name)
+    .bind(&snippet.content)
+    .bind(snippet_tags(snippet))
+    .bind(created_at)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// 返回是否找到了要修改的片段
+pub async fn update_snippet(
+    pool: &SqlitePool,
+    id: i64,
+    snippet: &SnippetInput,
+    updated_at: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE snippets SET name = ?, content = ?, tags = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(&snippet.name)
+    .bind(&snippet.content)
+    .bind(snippet_tags(snippet))
+    .bind(updated_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_snippet(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM snippets WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn touch_snippet(pool: &SqlitePool, id: i64, used_at: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE snippets SET last_used_at = ? WHERE id = ?")
+        .bind(used_at)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 和历史记录一样，标签以 JSON 数组保存
+fn snippet_tags(snippet: &SnippetInput) -> Option<String> {
+    (!snippet.tags.is_empty())
+        .then(|| serde_json::to_string(&snippet.tags).ok())
+        .flatten()
+}
+
 pub async fn clear_all(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM item_entities")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM item_paths").execute(pool).await?;
     sqlx::query("DELETE FROM clipboard_items")
         .execute(pool)
         .await?;
@@ -0,0 +1,340 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::models::{EntityKind, NewEntity};
+
+// 超长文本（日志、导出的表格）只取前 50 个实体，避免一次插入过多行
+const MAX_ENTITIES: usize = 50;
+
+/// 从文本中提取邮箱、电话号码和地址
+pub fn extract_entities(text: &str) -> Vec<NewEntity> {
+    let mut entities: Vec<NewEntity> = Vec::new();
+    let mut push = |kind: EntityKind, value: &str, normalized: String| {
+        if entities.len() >= MAX_ENTITIES
+            || entities
+                .iter()
+                .any(|entity| entity.kind == kind && entity.normalized == normalized)
+        {
+            return;
+        }
+        entities.push(NewEntity {
+            kind,
+            value: value.to_string(),
+            normalized,
+        });
+    };
+
+    for found in email_regex().find_iter(text) {
+        push(
+            EntityKind::Email,
+            found.as_str(),
+            normalize_email(found.as_str()),
+        );
+    }
+    for found in phone_regex().find_iter(text) {
+        // 邮箱里的数字串不算电话号码
+        if text[found.end()..].starts_with('@') {
+            continue;
+        }
+        if let Some(normalized) = normalize_phone(found.as_str()) {
+            push(EntityKind::Phone, found.as_str().trim(), normalized);
+        }
+    }
+    for address in find_addresses(text) {
+        let normalized = collapse_whitespace(&address);
+        push(EntityKind::Address, &address, normalized);
+    }
+    entities
+}
+
+/// 搜索电话号码时把用户输入的号码也规范成数字，忽略空格和横线
+pub fn phone_digits(query: &str) -> Option<String> {
+    let digits: String = query.chars().filter(char::is_ascii_digit).collect();
+    let only_phone_chars = query
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '+' | ' ' | '-' | '(' | ')' | '.'));
+    (only_phone_chars && digits.len() >= 3).then_some(digits)
+}
+
+fn email_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9\-]+(?:\.[A-Za-z0-9\-]+)*\.[A-Za-z]{2,}").unwrap()
+    })
+}
+
+fn phone_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        // 带国家码的国际号码、不带国家码的中国大陆手机号，以及带分隔符的本地号码：
+        // (555) 123-4567、555-123-4567、010-12345678、020 7946 0958、01 23 45 67 89。
+        // 本地号码必须有分隔符，避免把订单号之类的长数字当成电话
+        Regex::new(concat!(
+            r"(?:\+|\b00)\d[\d \-.()]{5,20}\d",
+            r"|\b1[3-9]\d[ \-]?\d{4}[ \-]?\d{4}\b",
+            r"|\(\d{2,4}\)\s?\d{3,4}[ \-]\d{4}\b",
+            r"|\b\d{3}[ \-]\d{3}[ \-]\d{4}\b",
+            r"|\b0\d{1,4}[ \-]\d{3,4}[ \-]?\d{3,4}\b",
+            r"|\b0\d(?:[ .]\d{2}){4}\b",
+        ))
+        .unwrap()
+    })
+}
+
+fn normalize_email(email: &str) -> String {
+    // 域名不区分大小写，本地部分按原样保留
+    match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{local}@{}", domain.to_ascii_lowercase()),
+        None => email.to_string(),
+    }
+}
+
+/// 规范成 E.164 格式，例如 +8613800138000；不带国家码的本地号码无法确定国家，只保留数字
+fn normalize_phone(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let digits: String = raw.chars().filter(char::is_ascii_digit).collect();
+    let international = if raw.starts_with('+') {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else if digits.len() == 11 && digits.starts_with('1') {
+        format!("86{digits}")
+    } else {
+        return (7..=12).contains(&digits.len()).then_some(digits);
+    };
+    // E.164 最长 15 位，国家码不以 0 开头
+    if !(8..=15).contains(&international.len()) || international.starts_with('0') {
+        return None;
+    }
+    Some(format!("+{international}"))
+}
+
+fn address_regexes() -> &'static [Regex] {
+    static REGEXES: OnceLock<Vec<Regex>> = OnceLock::new();
+    REGEXES.get_or_init(|| {
+        [
+            // 美国：城市, 州缩写 + ZIP，例如 Springfield, IL 62704
+            concat!(
+                r"\b\p{Lu}[\p{L}.' \-]*,\s*",
+                r"(?:AL|AK|AZ|AR|CA|CO|CT|DE|DC|FL|GA|HI|ID|IL|IN|IA|KS|KY|LA|ME|MD|MA|MI|MN|MS|MO|MT|",
+                r"NE|NV|NH|NJ|NM|NY|NC|ND|OH|OK|OR|PA|PR|RI|SC|SD|TN|TX|UT|VT|VA|WA|WV|WI|WY)",
+                r"\s+\d{5}(?:-\d{4})?\b",
+            ),
+            // 英国邮编，例如 SW1A 1AA
+            r"\b[A-Z]{1,2}\d[A-Z\d]?\s+\d[A-Z]{2}\b",
+            // 加拿大邮编，例如 K1A 0B1
+            r"\b[A-Z]\d[A-Z]\s?\d[A-Z]\d\b",
+            // 中国：省市区 + 路街 + 门牌号，或者带邮编
+            r"\p{Han}{2,}(?:省|市|自治区)\p{Han}*(?:市|区|县)[\p{Han}\d]*(?:路|街|道|巷|弄)[\d\-]+号",
+            r"邮编[:：]?\s*\d{6}",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect()
+    })
+}
+
+// 德国、法国等：五位邮编 + 城市名，例如 10115 Berlin。单独出现时和 12345 Items 分不开，
+// 要求同一段或上一行有街道
+fn postcode_city_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\b\d{5}\s+\p{Lu}\p{Ll}+").unwrap())
+}
+
+// 街道 + 门牌号：123 Main St、Hauptstraße 5、12 rue de la Paix
+fn street_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?i)\b\d+[a-z]?\s+(?:[\p{L}.'\-]+\s+){1,4}",
+            r"(?:st|street|ave|avenue|rd|road|blvd|boulevard|dr|drive|ln|lane|way|ct|court|pl|place|pkwy|parkway|hwy|highway)\b",
+            r"|\p{L}{2,}(?:straße|strasse|str\.|weg|allee|platz|gasse|ring|damm)\s*\d+",
+            r"|\b\d+(?:\s*(?:bis|ter))?,?\s+(?:rue|avenue|boulevard|bd|chemin|place|allée|impasse|quai)\s+\p{L}",
+        ))
+        .unwrap()
+    })
+}
+
+// 按行（以及行内的分号分段）查找带邮编或门牌号的地址，上一行是街道时一起作为地址
+fn find_addresses(text: &str) -> Vec<String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut addresses = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let segments: Vec<&str> = line
+            .split([';', '；', '|', '。'])
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        for segment in &segments {
+            if segment.chars().count() > 120 || email_regex().is_match(segment) {
+                continue;
+            }
+            let street = index
+                .checked_sub(1)
+                .map(|prev| lines[prev])
+                .filter(|prev| segments.len() == 1 && looks_like_street(prev));
+            let postcode_city = postcode_city_regex().is_match(segment)
+                && (street.is_some() || street_regex().is_match(segment));
+            if !postcode_city
+                && !address_regexes()
+                    .iter()
+                    .any(|regex| regex.is_match(segment))
+            {
+                continue;
+            }
+            match street {
+                Some(street) => addresses.push(format!("{street}\n{segment}")),
+                None => addresses.push(segment.to_string()),
+            }
+        }
+    }
+    addresses
+}
+
+fn looks_like_street(line: &str) -> bool {
+    line.chars().count() <= 100 && street_regex().is_match(line)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str, kind: EntityKind) -> Vec<String> {
+        extract_entities(text)
+            .into_iter()
+            .filter(|entity| entity.kind == kind)
+            .map(|entity| entity.normalized)
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_phone_numbers() {
+        let cases = [
+            ("+86 138 0013 8000", Some("+8613800138000")),
+            ("+1 (555) 123-4567", Some("+15551234567")),
+            ("0044 20 7946 0958", Some("+442079460958")),
+            ("13800138000", Some("+8613800138000")),
+            ("138-0013-8000", Some("+8613800138000")),
+            ("(555) 123-4567", Some("5551234567")),
+            ("010-12345678", Some("01012345678")),
+            ("01 23 45 67 89", Some("0123456789")),
+            ("+0 123 456 789", None),
+            ("+12 34", None),
+            ("123456", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_phone(raw).as_deref(), expected, "{raw}");
+        }
+    }
+
+    #[test]
+    fn extracts_phone_numbers() {
+        let cases = [
+            ("Call me at +86 138 0013 8000.", vec!["+8613800138000"]),
+            (
+                "手机 13800138000，座机 010-12345678",
+                vec!["+8613800138000", "01012345678"],
+            ),
+            ("Office: (555) 123-4567", vec!["5551234567"]),
+            ("London 020 7946 0958", vec!["02079460958"]),
+            ("Paris 01 23 45 67 89", vec!["0123456789"]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(found(text, EntityKind::Phone), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn ignores_order_numbers_and_email_digits() {
+        let cases = [
+            "Order #202310181234 has shipped",
+            "订单号 2023101812345678",
+            "Tracking 1Z999AA10123456784",
+            "Invoice 12345678",
+            "Reply to 13800138000@example.com",
+            "Contact 5551234567@sms.example.org",
+            "Version 1.2.3.4 released",
+            "ID 00000",
+        ];
+        for text in cases {
+            assert!(found(text, EntityKind::Phone).is_empty(), "{text}");
+        }
+    }
+
+    #[test]
+    fn extracts_emails() {
+        assert_eq!(
+            found(
+                "Mail Ann.Lee@Example.COM or bob@mail.example.org",
+                EntityKind::Email
+            ),
+            vec!["Ann.Lee@example.com", "bob@mail.example.org"]
+        );
+    }
+
+    #[test]
+    fn extracts_addresses() {
+        let cases = [
+            (
+                "123 Main St\nSpringfield, IL 62704",
+                "123 Main St, Springfield, IL 62704",
+            ),
+            ("Springfield, IL 62704-1234", "Springfield, IL 62704-1234"),
+            (
+                "10 Downing Street\nLondon SW1A 2AA",
+                "10 Downing Street, London SW1A 2AA",
+            ),
+            ("Ottawa, ON K1A 0B1", "Ottawa, ON K1A 0B1"),
+            ("Hauptstraße 5, 10115 Berlin", "Hauptstraße 5, 10115 Berlin"),
+            ("Hauptstraße 5\n10115 Berlin", "Hauptstraße 5, 10115 Berlin"),
+            (
+                "12 rue de la Paix\n75002 Paris",
+                "12 rue de la Paix, 75002 Paris",
+            ),
+            ("北京市朝阳区建国路88号", "北京市朝阳区建国路88号"),
+            (
+                "广东省深圳市南山区科技园路1号",
+                "广东省深圳市南山区科技园路1号",
+            ),
+            ("邮编：100020", "邮编：100020"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(found(text, EntityKind::Address), vec![expected], "{text}");
+        }
+    }
+
+    #[test]
+    fn ignores_non_addresses() {
+        let cases = [
+            "12345 Items",
+            "Showing 12345 Results",
+            "We shipped 10115 Boxes today",
+            "Order #202310181234 has shipped",
+            "Email anna@example.de about 10115 Berlin",
+            "The meeting is in Berlin on Monday",
+        ];
+        for text in cases {
+            assert!(found(text, EntityKind::Address).is_empty(), "{text}");
+        }
+    }
+
+    #[test]
+    fn phone_query_digits() {
+        assert_eq!(
+            phone_digits("138 0013-8000").as_deref(),
+            Some("13800138000")
+        );
+        assert_eq!(phone_digits("(555)").as_deref(), Some("555"));
+        assert_eq!(phone_digits("12"), None);
+        assert_eq!(phone_digits("order 123"), None);
+    }
+}
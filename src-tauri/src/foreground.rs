@@ -0,0 +1,138 @@
+/// 当前前台窗口所属进程的可执行文件名，例如 Code.exe
+#[cfg(target_os = "windows")]
+pub fn foreground_app() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+        let path = String::from_utf16_lossy(&buffer[..size as usize]);
+        path.rsplit(['\\', '/']).next().map(str::to_string)
+    }
+}
+
+/// X11 下通过 _NET_WM_PID 找到活动窗口的进程，Wayland 下无法获取
+#[cfg(target_os = "linux")]
+pub fn foreground_app() -> Option<String> {
+    use std::sync::Mutex;
+
+    // 每次剪贴板变化都会调用，复用同一个连接；出错时丢掉，下次重新连接
+    static X11: Mutex<Option<x11::ActiveWindow>> = Mutex::new(None);
+
+    if crate::paste_input::is_wayland() {
+        return None;
+    }
+    let pid = {
+        let mut x11 = X11.lock().unwrap();
+        if x11.is_none() {
+            *x11 = x11::ActiveWindow::connect().ok();
+        }
+        match x11.as_ref()?.pid() {
+            Ok(pid) => pid?,
+            Err(_) => {
+                *x11 = None;
+                return None;
+            }
+        }
+    };
+    // 和 Windows 一样返回可执行文件名，读不到时退回到进程名
+    match std::fs::read_link(format!("/proc/{pid}/exe")) {
+        Ok(path) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        Err(_) => std::fs::read_to_string(format!("/proc/{pid}/comm"))
+            .ok()
+            .map(|name| name.trim().to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    pub struct ActiveWindow {
+        conn: RustConnection,
+        root: Window,
+        active_window: Atom,
+        wm_pid: Atom,
+    }
+
+    impl ActiveWindow {
+        pub fn connect() -> Result<Self, String> {
+            let (conn, screen) = x11rb::connect(None).map_err(|err| err.to_string())?;
+            let root = conn.setup().roots[screen].root;
+            let atom = |name: &[u8]| -> Result<Atom, String> {
+                Ok(conn
+                    .intern_atom(false, name)
+                    .map_err(|err| err.to_string())?
+                    .reply()
+                    .map_err(|err| err.to_string())?
+                    .atom)
+            };
+            let active_window = atom(b"_NET_ACTIVE_WINDOW")?;
+            let wm_pid = atom(b"_NET_WM_PID")?;
+            Ok(Self {
+                conn,
+                root,
+                active_window,
+                wm_pid,
+            })
+        }
+
+        /// 活动窗口所属进程的 pid；没有活动窗口或窗口没有设置 _NET_WM_PID 时返回 None
+        pub fn pid(&self) -> Result<Option<u32>, String> {
+            match self.property(self.root, self.active_window, AtomEnum::WINDOW)? {
+                Some(window) if window != 0 => {
+                    self.property(window, self.wm_pid, AtomEnum::CARDINAL)
+                }
+                _ => Ok(None),
+            }
+        }
+
+        fn property(
+            &self,
+            window: Window,
+            atom: Atom,
+            kind: AtomEnum,
+        ) -> Result<Option<u32>, String> {
+            let reply = self
+                .conn
+                .get_property(false, window, atom, kind, 0, 1)
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?;
+            Ok(reply.value32().and_then(|mut values| values.next()))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn foreground_app() -> Option<String> {
+    None
+}
@@ -0,0 +1,225 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{imageops, DynamicImage, ImageFormat, Rgba, RgbaImage};
+use serde::Deserialize;
+
+use crate::clipboard::MAX_IMAGE_SIZE;
+
+/// 图片上的矩形区域，坐标以像素为单位
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactStyle {
+    /// 用黑色矩形覆盖
+    #[default]
+    Box,
+    /// 高斯模糊
+    Blur,
+}
+
+/// 对历史图片的一次编辑操作，多个操作按顺序执行
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum ImageOp {
+    Crop(Rect),
+    #[serde(rename_all = "camelCase")]
+    Resize {
+        width: u32,
+        height: u32,
+        /// 为 true 时按比例缩放到 width x height 之内
+        #[serde(default)]
+        keep_aspect: bool,
+    },
+    Rotate {
+        degrees: i32,
+    },
+    Grayscale,
+    Redact {
+        rects: Vec<Rect>,
+        #[serde(default)]
+        style: RedactStyle,
+    },
+}
+
+pub fn apply_all(mut image: RgbaImage, ops: &[ImageOp]) -> Result<RgbaImage, String> {
+    for op in ops {
+        image = apply(image, op)?;
+    }
+    let size = image.width() as usize * image.height() as usize * 4;
+    if size > MAX_IMAGE_SIZE {
+        return Err(format!(
+            "编辑后的图片过大: {}x{}",
+            image.width(),
+            image.height()
+        ));
+    }
+    Ok(image)
+}
+
+fn apply(image: RgbaImage, op: &ImageOp) -> Result<RgbaImage, String> {
+    match op {
+        ImageOp::Crop(rect) => {
+            let rect =
+                clamp_rect(&image, rect).ok_or_else(|| "裁剪区域超出图片范围".to_string())?;
+            Ok(imageops::crop_imm(&image, rect.x, rect.y, rect.width, rect.height).to_image())
+        }
+        ImageOp::Resize {
+            width,
+            height,
+            keep_aspect,
+        } => {
+            if *width == 0 || *height == 0 {
+                return Err("缩放后的尺寸不能为 0".to_string());
+            }
+            let (width, height) = if *keep_aspect {
+                fit_within(image.width(), image.height(), *width, *height)
+            } else {
+                (*width, *height)
+            };
+            if width as usize * height as usize * 4 > MAX_IMAGE_SIZE {
+                return Err(format!("缩放后的图片过大: {width}x{height}"));
+            }
+            Ok(imageops::resize(
+                &image,
+                width,
+                height,
+                imageops::FilterType::Lanczos3,
+            ))
+        }
+        ImageOp::Rotate { degrees } => match degrees.rem_euclid(360) {
+            0 => Ok(image),
+            90 => Ok(imageops::rotate90(&image)),
+            180 => Ok(imageops::rotate180(&image)),
+            270 => Ok(imageops::rotate270(&image)),
+            _ => Err(format!("只支持旋转 90 度的倍数: {degrees}")),
+        },
+        ImageOp::Grayscale => {
+            let gray = imageops::grayscale_alpha(&image);
+            Ok(image::DynamicImage::ImageLumaA8(gray).into_rgba8())
+        }
+        ImageOp::Redact { rects, style } => {
+            let mut image = image;
+            for rect in rects {
+                if let Some(rect) = clamp_rect(&image, rect) {
+                    redact(&mut image, rect, *style);
+                }
+            }
+            Ok(image)
+        }
+    }
+}
+
+// 把矩形裁到图片范围内，完全在图片外时返回 None
+fn clamp_rect(image: &RgbaImage, rect: &Rect) -> Option<Rect> {
+    if rect.x >= image.width() || rect.y >= image.height() {
+        return None;
+    }
+    let width = rect.width.min(image.width() - rect.x);
+    let height = rect.height.min(image.height() - rect.y);
+    (width > 0 && height > 0).then_some(Rect {
+        x: rect.x,
+        y: rect.y,
+        width,
+        height,
+    })
+}
+
+fn fit_within(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    let scale = f64::min(
+        max_width as f64 / width as f64,
+        max_height as f64 / height as f64,
+    );
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+fn redact(image: &mut RgbaImage, rect: Rect, style: RedactStyle) {
+    match style {
+        RedactStyle::Box => {
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+        RedactStyle::Blur => {
+            // 模糊半径跟区域大小挂钩，保证文字无法辨认
+            let sigma = (rect.width.min(rect.height) as f32 / 3.0).max(8.0);
+            let region =
+                imageops::crop_imm(image, rect.x, rect.y, rect.width, rect.height).to_image();
+            let blurred = imageops::blur(&region, sigma);
+            imageops::replace(image, &blurred, rect.x as i64, rect.y as i64);
+        }
+    }
+}
+
+/// 导出图片时支持的文件格式
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageFileFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Bmp,
+}
+
+// JPEG 未指定质量时的默认值
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+impl ImageFileFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+            Self::Bmp => "bmp",
+        }
+    }
+
+    /// 根据文件扩展名推断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            "bmp" => Some(Self::Bmp),
+            _ => None,
+        }
+    }
+}
+
+/// 编码成指定格式的文件内容；quality 只对 JPEG 生效（1-100）
+pub fn encode(
+    image: RgbaImage,
+    format: ImageFileFormat,
+    quality: Option<u8>,
+) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    let dynamic = DynamicImage::ImageRgba8(image);
+    let result = match format {
+        ImageFileFormat::Png => dynamic.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png),
+        // JPEG 不支持透明通道，先转成 RGB
+        ImageFileFormat::Jpeg => {
+            let quality = quality.unwrap_or(DEFAULT_JPEG_QUALITY).clamp(1, 100);
+            let encoder = JpegEncoder::new_with_quality(&mut buffer, quality);
+            DynamicImage::ImageRgb8(dynamic.into_rgb8()).write_with_encoder(encoder)
+        }
+        ImageFileFormat::Webp => dynamic.write_to(&mut Cursor::new(&mut buffer), ImageFormat::WebP),
+        ImageFileFormat::Bmp => dynamic.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Bmp),
+    };
+    result.map_err(|err| format!("图片编码失败: {err}"))?;
+    Ok(buffer)
+}
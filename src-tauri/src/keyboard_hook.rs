@@ -1,16 +1,31 @@
 #[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "windows")]
 use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(target_os = "windows")]
 use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+#[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    GetAsyncKeyState, GetKeyState, GetKeyboardLayout, ToUnicodeEx, VIRTUAL_KEY, VK_BACK,
+    VK_CAPITAL, VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MENU, VK_RCONTROL,
+    VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SHIFT, VK_V,
 };
+#[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::{
-    CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT, WH_KEYBOARD_LL,
-    WM_KEYDOWN, WM_SYSKEYDOWN,
+    CallNextHookEx, GetForegroundWindow, GetWindowThreadProcessId, SetWindowsHookExW,
+    UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, WH_KEYBOARD_LL, WM_KEYDOWN,
+    WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
 };
 
+#[cfg(target_os = "windows")]
+use crate::abbreviations;
+
+#[cfg(target_os = "windows")]
 type HotkeyCallback = Arc<dyn Fn() + Send + Sync + 'static>;
+#[cfg(target_os = "windows")]
+type PasteInterceptor = Arc<dyn Fn(bool) -> bool + Send + Sync + 'static>;
 
+#[cfg(target_os = "windows")]
 struct HookState {
     callback: Option<HotkeyCallback>,
     key_code: u32,
@@ -21,10 +36,25 @@ struct HookState {
 }
 
 // 使用 OnceLock 来存储全局状态
+#[cfg(target_os = "windows")]
 static HOOK_STATE: OnceLock<Mutex<HookState>> = OnceLock::new();
+#[cfg(target_os = "windows")]
 static HOOK_HANDLE: OnceLock<Mutex<Option<isize>>> = OnceLock::new();
+// Ctrl+V 送达目标窗口之前调用，用于队列粘贴
+#[cfg(target_os = "windows")]
+static PASTE_INTERCEPTOR: OnceLock<Mutex<Option<PasteInterceptor>>> = OnceLock::new();
+// V 键是否处于按下状态，用来区分按住不放时的自动重复
+#[cfg(target_os = "windows")]
+static V_HELD: AtomicBool = AtomicBool::new(false);
 
+#[cfg(target_os = "windows")]
 unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 && (wparam.0 as u32 == WM_KEYUP || wparam.0 as u32 == WM_SYSKEYUP) {
+        let kb_struct = *(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if kb_struct.vkCode == VK_V.0 as u32 && (kb_struct.flags.0 & LLKHF_INJECTED.0) == 0 {
+            V_HELD.store(false, Ordering::Relaxed);
+        }
+    }
     if code >= 0 && (wparam.0 as u32 == WM_KEYDOWN || wparam.0 as u32 == WM_SYSKEYDOWN) {
         let kb_struct = *(lparam.0 as *const KBDLLHOOKSTRUCT);
         let vk_code = kb_struct.vkCode;
@@ -46,6 +76,7 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
                     && win_pressed == state.win
                 {
                     // 触发回调
+                    abbreviations::reset();
                     if let Some(callback) = &state.callback {
                         callback();
                     }
@@ -54,11 +85,90 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
                 }
             }
         }
+
+        // 模拟出来的按键（包括自己发送的 Ctrl+V）不拦截
+        let injected = (kb_struct.flags.0 & LLKHF_INJECTED.0) != 0;
+        if vk_code == VK_V.0 as u32 && !injected {
+            let repeat = V_HELD.swap(true, Ordering::Relaxed);
+            let interceptor = PASTE_INTERCEPTOR
+                .get()
+                .and_then(|interceptor| interceptor.lock().ok()?.clone());
+            if let Some(interceptor) = interceptor {
+                let ctrl_pressed = (GetAsyncKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0;
+                let alt_pressed = (GetAsyncKeyState(VK_MENU.0 as i32) as u16 & 0x8000) != 0;
+                let win_pressed = (GetAsyncKeyState(VK_LWIN.0 as i32) as u16 & 0x8000) != 0
+                    || (GetAsyncKeyState(VK_RWIN.0 as i32) as u16 & 0x8000) != 0;
+                if ctrl_pressed && !alt_pressed && !win_pressed && interceptor(repeat) {
+                    return LRESULT(1);
+                }
+            }
+        }
+
+        if !injected {
+            track_abbreviation(&kb_struct);
+        }
     }
 
     CallNextHookEx(None, code, wparam, lparam)
 }
 
+// 把按键记进缩写缓冲区
+#[cfg(target_os = "windows")]
+unsafe fn track_abbreviation(kb_struct: &KBDLLHOOKSTRUCT) {
+    let pressed = |key: VIRTUAL_KEY| (GetAsyncKeyState(key.0 as i32) as u16 & 0x8000) != 0;
+    match VIRTUAL_KEY(kb_struct.vkCode as u16) {
+        VK_BACK => abbreviations::backspace(),
+        // 单独按下修饰键不影响缓冲区
+        VK_SHIFT | VK_LSHIFT | VK_RSHIFT | VK_CONTROL | VK_LCONTROL | VK_RCONTROL | VK_MENU
+        | VK_LMENU | VK_RMENU | VK_CAPITAL => {}
+        _ => {
+            let ctrl = pressed(VK_CONTROL);
+            let alt = pressed(VK_MENU);
+            // 同时按住 Ctrl 和 Alt 相当于 AltGr，仍然是在输入字符
+            if pressed(VK_LWIN) || pressed(VK_RWIN) || ctrl != alt {
+                abbreviations::reset();
+                return;
+            }
+            match typed_char(kb_struct, ctrl && alt) {
+                Some(ch) => abbreviations::push_char(ch),
+                None => abbreviations::reset(),
+            }
+        }
+    }
+}
+
+// 按目标窗口的键盘布局把按键翻译成字符
+#[cfg(target_os = "windows")]
+unsafe fn typed_char(kb_struct: &KBDLLHOOKSTRUCT, alt_gr: bool) -> Option<char> {
+    // 低级钩子里 GetKeyboardState 拿不到其他程序的按键状态，只能自己拼出来
+    let mut state = [0u8; 256];
+    if (GetAsyncKeyState(VK_SHIFT.0 as i32) as u16 & 0x8000) != 0 {
+        state[VK_SHIFT.0 as usize] = 0x80;
+    }
+    if GetKeyState(VK_CAPITAL.0 as i32) & 1 != 0 {
+        state[VK_CAPITAL.0 as usize] = 0x01;
+    }
+    if alt_gr {
+        state[VK_CONTROL.0 as usize] = 0x80;
+        state[VK_MENU.0 as usize] = 0x80;
+    }
+    let layout = GetKeyboardLayout(GetWindowThreadProcessId(GetForegroundWindow(), None));
+    let mut buffer = [0u16; 4];
+    // 0x4：不改变系统的死键状态，否则会吞掉用户接下来输入的重音字符
+    let len = ToUnicodeEx(
+        kb_struct.vkCode,
+        kb_struct.scanCode,
+        &state,
+        &mut buffer,
+        0x4,
+        layout,
+    );
+    if len != 1 {
+        return None;
+    }
+    char::from_u32(u32::from(buffer[0]))
+}
+
 #[cfg(target_os = "windows")]
 pub fn register_hotkey<F>(
     key_code: u32,
@@ -120,6 +230,20 @@ pub fn unregister_hotkey() {
     }
 }
 
+/// 设置 Ctrl+V 拦截回调，参数表示是否为按住不放产生的重复按键，返回 true 时吞掉这次按键。
+/// 回调在钩子线程上同步执行，只能做判断和通知，不能读写剪贴板
+#[cfg(target_os = "windows")]
+pub fn set_paste_interceptor<F>(callback: F)
+where
+    F: Fn(bool) -> bool + Send + Sync + 'static,
+{
+    PASTE_INTERCEPTOR
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap()
+        .replace(Arc::new(callback));
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn register_hotkey<F>(
     _key_code: u32,
@@ -137,3 +261,335 @@ where
 
 #[cfg(not(target_os = "windows"))]
 pub fn unregister_hotkey() {}
+
+/// 其他平台没有全局的 Ctrl+V 拦截，连续粘贴只能通过 paste_queue_next 命令手动切换到下一条
+#[cfg(not(target_os = "windows"))]
+pub fn set_paste_interceptor<F>(_callback: F)
+where
+    F: Fn(bool) -> bool + Send + Sync + 'static,
+{
+}
+
+/// 开始监听输入的缩写；Windows 上没有单独的监听，由 register_hotkey 安装的键盘钩子处理
+/// （启动时总会注册呼出快捷键，注册失败时缩写和连续粘贴都不可用），
+/// Linux 上 X11 通过 XRecord 读取按键，Wayland 下直接读取 evdev 键盘设备
+pub fn start_abbreviation_listener() {
+    #[cfg(target_os = "linux")]
+    {
+        let result = if crate::paste_input::is_wayland() {
+            evdev::start()
+        } else {
+            record::start()
+        };
+        if let Err(err) = result {
+            crate::clipboard::log_line(&format!("abbreviation: listener unavailable: {err}"));
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod record {
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::record::{self, ConnectionExt as _};
+    use x11rb::protocol::xproto::{
+        ConnectionExt as _, Mapping, BUTTON_PRESS_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+    };
+    use x11rb::protocol::Event;
+
+    use crate::abbreviations;
+    use crate::clipboard::log_line;
+
+    // XRecord 回复中来自服务器的数据
+    const FROM_SERVER: u8 = 0;
+
+    // 按键映射在启动和布局变化时读取，只使用第一个布局组
+    struct Keyboard {
+        min_keycode: u8,
+        per_keycode: usize,
+        keysyms: Vec<u32>,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        logo: bool,
+        level3: bool,
+        caps_lock: bool,
+    }
+
+    impl Keyboard {
+        fn load(conn: &impl Connection) -> Result<Self, String> {
+            let mut keyboard = Self {
+                min_keycode: 0,
+                per_keycode: 1,
+                keysyms: Vec::new(),
+                shift: false,
+                ctrl: false,
+                alt: false,
+                logo: false,
+                level3: false,
+                caps_lock: false,
+            };
+            keyboard.reload(conn)?;
+            Ok(keyboard)
+        }
+
+        // 只替换映射，保留修饰键的按下状态
+        fn reload(&mut self, conn: &impl Connection) -> Result<(), String> {
+            let setup = conn.setup();
+            let (min, max) = (setup.min_keycode, setup.max_keycode);
+            let mapping = conn
+                .get_keyboard_mapping(min, max - min + 1)
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?;
+            self.min_keycode = min;
+            self.per_keycode = usize::from(mapping.keysyms_per_keycode).max(1);
+            self.keysyms = mapping.keysyms;
+            Ok(())
+        }
+
+        fn keysym(&self, keycode: u8, column: usize) -> u32 {
+            if column >= self.per_keycode {
+                return 0;
+            }
+            let index = usize::from(keycode.saturating_sub(self.min_keycode)) * self.per_keycode;
+            self.keysyms.get(index + column).copied().unwrap_or(0)
+        }
+
+        fn handle(&mut self, event: u8, keycode: u8) {
+            let pressed = event == KEY_PRESS_EVENT;
+            match self.keysym(keycode, 0) {
+                0xffe1 | 0xffe2 => self.shift = pressed,
+                0xffe3 | 0xffe4 => self.ctrl = pressed,
+                0xffe7..=0xffea => self.alt = pressed,
+                0xffeb | 0xffec => self.logo = pressed,
+                // ISO_Level3_Shift（AltGr）和 Mode_switch
+                0xfe03 | 0xff7e => self.level3 = pressed,
+                0xffe5 => self.caps_lock ^= pressed,
+                _ if !pressed => {}
+                0xff08 => abbreviations::backspace(),
+                _ if self.ctrl || self.alt || self.logo => abbreviations::reset(),
+                _ => match self.typed_char(keycode) {
+                    Some(ch) => abbreviations::push_char(ch),
+                    None => abbreviations::reset(),
+                },
+            }
+        }
+
+        fn typed_char(&self, keycode: u8) -> Option<char> {
+            // 核心协议的映射里第 5、6 列是 AltGr 层
+            let column = if self.level3 { 4 } else { 0 };
+            let lower = self.keysym(keycode, column);
+            let upper = self.keysym(keycode, column + 1);
+            let letter = keysym_char(lower).is_some_and(char::is_alphabetic);
+            let shifted = self.shift ^ (self.caps_lock && letter);
+            if !shifted {
+                return keysym_char(lower);
+            }
+            match upper {
+                // 只列出小写字母时大写由客户端自己转换
+                0 => keysym_char(lower)?.to_uppercase().next(),
+                upper => keysym_char(upper),
+            }
+        }
+    }
+
+    fn keysym_char(keysym: u32) -> Option<char> {
+        match keysym {
+            0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+            0x0100_0000..=0x0110_ffff => char::from_u32(keysym - 0x0100_0000),
+            // 功能键
+            _ => None,
+        }
+    }
+
+    pub fn start() -> Result<(), String> {
+        // 录制要用两个连接，一个控制录制，一个接收数据
+        let connect = || {
+            x11rb::connect(None)
+                .map(|(conn, _)| conn)
+                .map_err(|err| format!("无法连接 X 服务器: {err}"))
+        };
+        let (control, data) = (connect()?, connect()?);
+        if control
+            .extension_information(record::X11_EXTENSION_NAME)
+            .map_err(|err| err.to_string())?
+            .is_none()
+        {
+            return Err("X 服务器不支持 RECORD 扩展".to_string());
+        }
+        let mut keyboard = Keyboard::load(&control)?;
+
+        let context = control.generate_id().map_err(|err| err.to_string())?;
+        let empty = record::Range8 { first: 0, last: 0 };
+        let empty_ext = record::ExtRange {
+            major: empty,
+            minor: record::Range16 { first: 0, last: 0 },
+        };
+        let range = record::Range {
+            core_requests: empty,
+            core_replies: empty,
+            ext_requests: empty_ext,
+            ext_replies: empty_ext,
+            delivered_events: empty,
+            // 鼠标点击会移动光标，也要清空缓冲区
+            device_events: record::Range8 {
+                first: KEY_PRESS_EVENT,
+                last: BUTTON_PRESS_EVENT,
+            },
+            errors: empty,
+            client_started: false,
+            client_died: false,
+        };
+        control
+            .record_create_context(context, 0, &[record::CS::ALL_CLIENTS.into()], &[range])
+            .map_err(|err| err.to_string())?
+            .check()
+            .map_err(|err| err.to_string())?;
+
+        std::thread::spawn(move || {
+            let replies = match data.record_enable_context(context) {
+                Ok(replies) => replies,
+                Err(err) => {
+                    log_line(&format!("abbreviation: failed to enable record: {err}"));
+                    return;
+                }
+            };
+            for reply in replies {
+                let reply = match reply {
+                    Ok(reply) => reply,
+                    Err(err) => {
+                        log_line(&format!("abbreviation: record stopped: {err}"));
+                        break;
+                    }
+                };
+                // setxkbmap 等切换布局后服务器向所有客户端发送 MappingNotify，在控制连接上检查
+                while let Ok(Some(event)) = control.poll_for_event() {
+                    if let Event::MappingNotify(notify) = event {
+                        if notify.request == Mapping::KEYBOARD {
+                            if let Err(err) = keyboard.reload(&control) {
+                                log_line(&format!("abbreviation: failed to reload keymap: {err}"));
+                            }
+                        }
+                    }
+                }
+                if reply.category != FROM_SERVER || reply.client_swapped {
+                    continue;
+                }
+                // 设备事件固定 32 字节，第 1 字节是类型，第 2 字节是 keycode
+                for event in reply.data.chunks_exact(32) {
+                    match event[0] & 0x7f {
+                        kind @ (KEY_PRESS_EVENT | KEY_RELEASE_EVENT) => {
+                            keyboard.handle(kind, event[1])
+                        }
+                        BUTTON_PRESS_EVENT => abbreviations::reset(),
+                        _ => {}
+                    }
+                }
+            }
+            drop(control);
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod evdev {
+    use std::ffi::CStr;
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+
+    use crate::abbreviations;
+    use crate::clipboard::log_line;
+    use crate::paste_input;
+
+    const EV_KEY: u16 = 0x01;
+    const KEY_A: usize = 30;
+    const EVIOCGNAME: libc::Ioctl = libc::_IOR::<[u8; 256]>(b'E' as u32, 0x06);
+    const EVIOCGBIT_KEY: libc::Ioctl = libc::_IOR::<[u8; 96]>(b'E' as u32, 0x20 + EV_KEY as u32);
+    // paste_input 创建的虚拟键盘，不监听自己发出的按键
+    const OWN_DEVICE: &[u8] = b"paste_app keyboard";
+
+    #[derive(Default)]
+    struct Modifiers {
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        logo: bool,
+        caps_lock: bool,
+    }
+
+    pub fn start() -> Result<(), String> {
+        let entries = std::fs::read_dir("/dev/input").map_err(|err| err.to_string())?;
+        let mut count = 0;
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with("event") {
+                continue;
+            }
+            let Ok(file) = File::open(entry.path()) else {
+                continue;
+            };
+            if is_keyboard(&file) {
+                std::thread::spawn(move || read_events(file));
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Err("没有可读取的键盘设备，需要把当前用户加入 input 组".to_string());
+        }
+        Ok(())
+    }
+
+    fn is_keyboard(file: &File) -> bool {
+        let fd = file.as_raw_fd();
+        let mut name = [0u8; 256];
+        let mut keys = [0u8; 96];
+        let ok = unsafe {
+            libc::ioctl(fd, EVIOCGNAME, name.as_mut_ptr()) >= 0
+                && libc::ioctl(fd, EVIOCGBIT_KEY, keys.as_mut_ptr()) >= 0
+        };
+        let own = CStr::from_bytes_until_nul(&name).is_ok_and(|name| name.to_bytes() == OWN_DEVICE);
+        // 有字母键的才是键盘，电源键、媒体键之类的设备跳过
+        ok && !own && keys[KEY_A / 8] & (1 << (KEY_A % 8)) != 0
+    }
+
+    fn read_events(mut file: File) {
+        let mut modifiers = Modifiers::default();
+        let mut buffer = [0u8; std::mem::size_of::<libc::input_event>()];
+        loop {
+            if let Err(err) = file.read_exact(&mut buffer) {
+                log_line(&format!("abbreviation: keyboard device closed: {err}"));
+                return;
+            }
+            let event: libc::input_event =
+                unsafe { std::ptr::read_unaligned(buffer.as_ptr().cast()) };
+            if event.type_ == EV_KEY {
+                handle(&mut modifiers, event.code, event.value);
+            }
+        }
+    }
+
+    // value 为 0 表示松开，1 表示按下，2 表示按住不放时的重复
+    fn handle(modifiers: &mut Modifiers, code: u16, value: i32) {
+        let pressed = value != 0;
+        match code {
+            42 | 54 => modifiers.shift = pressed,
+            29 | 97 => modifiers.ctrl = pressed,
+            56 | 100 => modifiers.alt = pressed,
+            125 | 126 => modifiers.logo = pressed,
+            58 => modifiers.caps_lock ^= value == 1,
+            _ if !pressed => {}
+            14 => abbreviations::backspace(),
+            _ if modifiers.ctrl || modifiers.alt || modifiers.logo => abbreviations::reset(),
+            // 读不到合成器的布局设置，按 US 布局翻译
+            _ => {
+                let letter = paste_input::evdev_char(code, false).is_some_and(char::is_alphabetic);
+                let shifted = modifiers.shift ^ (modifiers.caps_lock && letter);
+                match paste_input::evdev_char(code, shifted) {
+                    Some(ch) => abbreviations::push_char(ch),
+                    None => abbreviations::reset(),
+                }
+            }
+        }
+    }
+}
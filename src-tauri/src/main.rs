@@ -1,20 +1,56 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod abbreviations;
 mod classify;
 mod clipboard;
+mod code;
+mod color;
 mod db;
+mod entities;
+mod foreground;
+mod image_ops;
 mod keyboard_hook;
 mod models;
+mod ocr;
+mod paste;
+mod paste_input;
+mod paste_profiles;
+mod paste_queue;
+mod paths;
+mod phash;
+mod rules;
+mod settings;
+mod snippets;
+mod structured;
+mod transforms;
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 use arboard::ImageData;
 use sqlx::SqlitePool;
 use tauri::{Manager, State};
 use tauri_plugin_autostart::ManagerExt;
 
-use crate::models::{ClipboardItem, ClipboardItemRow};
+use crate::abbreviations::{Abbreviation, AbbreviationTarget};
+use crate::classify::ClassifierInfo;
+use crate::color::ColorNotation;
+use crate::image_ops::{ImageFileFormat, ImageOp};
+use crate::models::{
+    ClipboardFormat, ClipboardItem, ClipboardItemRow, Entity, EntityKind, EntityKindCount,
+    ImageExportSummary, LanguageCount, LinkHostCount, NewClipboardItem, PathEntry, Snippet,
+    SnippetInput,
+};
+use crate::paste::{MergeSeparator, PasteMode};
+use crate::paste_input::PasteChord;
+use crate::paste_profiles::PasteProfile;
+use crate::paste_queue::{QueueOrder, QueueStatus};
+use crate::settings::Settings;
+use crate::snippets::{ExpandContext, Expansion};
+use crate::structured::StructuredOutput;
+use crate::transforms::Transform;
 
 fn parse_hotkey(hotkey: &str) -> (u32, bool, bool, bool, bool) {
     let parts: Vec<&str> = hotkey.split('+').map(|s| s.trim()).collect();
@@ -98,13 +134,441 @@ async fn search_history(
 }
 
 #[tauri::command]
-async fn set_clipboard(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+async fn list_link_hosts(state: State<'_, AppState>) -> Result<Vec<LinkHostCount>, String> {
+    db::list_link_hosts(&state.db)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn list_history_by_link_host(
+    state: State<'_, AppState>,
+    host: String,
+    limit: Option<i64>,
+) -> Result<Vec<ClipboardItem>, String> {
+    let limit = limit.unwrap_or(200).clamp(1, 1000);
+    let rows = db::list_items_by_link_host(&state.db, host.trim(), limit)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(rows.into_iter().map(ClipboardItem::from_row).collect())
+}
+
+#[tauri::command]
+async fn list_languages(state: State<'_, AppState>) -> Result<Vec<LanguageCount>, String> {
+    db::list_languages(&state.db)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn list_history_by_language(
+    state: State<'_, AppState>,
+    language: String,
+    limit: Option<i64>,
+) -> Result<Vec<ClipboardItem>, String> {
+    let limit = limit.unwrap_or(200).clamp(1, 1000);
+    let rows = db::list_items_by_language(&state.db, language.trim(), limit)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(rows.into_iter().map(ClipboardItem::from_row).collect())
+}
+
+#[tauri::command]
+async fn list_entity_kinds(state: State<'_, AppState>) -> Result<Vec<EntityKindCount>, String> {
+    db::list_entity_kinds(&state.db)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn list_item_entities(state: State<'_, AppState>, id: i64) -> Result<Vec<Entity>, String> {
+    db::list_item_entities(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn list_history_by_entity(
+    state: State<'_, AppState>,
+    kind: Option<EntityKind>,
+    query: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<ClipboardItem>, String> {
+    let limit = limit.unwrap_or(200).clamp(1, 1000);
+    let query = query.unwrap_or_default();
+    let rows = db::list_items_by_entity(&state.db, kind, &query, limit)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(rows.into_iter().map(ClipboardItem::from_row).collect())
+}
+
+// 路径状态超过这个时间才在展示时重新检查
+const PATH_REFRESH_MS: i64 = 30_000;
+
+#[tauri::command]
+async fn list_item_paths(state: State<'_, AppState>, id: i64) -> Result<Vec<PathEntry>, String> {
+    let entries = db::list_item_paths(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?;
+    let now = clipboard::now_ms();
+    let fresh = entries
+        .iter()
+        .all(|entry| entry.checked_at.is_some_and(|at| now - at < PATH_REFRESH_MS));
+    if !entries.is_empty() && fresh {
+        return Ok(entries);
+    }
+
+    let list: Vec<String> = if entries.is_empty() {
+        // 升级前保存的文件记录没有路径明细，展示时按需解析
+        let row = db::get_item(&state.db, id)
+            .await
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| "记录不存在".to_string())?;
+        match (row.format, row.file_path) {
+            (ClipboardFormat::File, Some(file_path)) => paths::parse_path_list(&file_path)
+                .unwrap_or_else(|| vec![file_path.trim().to_string()]),
+            _ => return Ok(entries),
+        }
+    } else {
+        entries.into_iter().map(|entry| entry.path).collect()
+    };
+    // 网络路径可能很慢，放到阻塞线程里检查
+    let checked = tauri::async_runtime::spawn_blocking(move || {
+        list.iter()
+            .map(|path| paths::inspect(path, true, now))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    db::save_item_paths(&state.db, id, &checked)
+        .await
+        .map_err(|err| err.to_string())?;
+    db::list_item_paths(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn find_similar_images(
+    state: State<'_, AppState>,
+    id: i64,
+    threshold: Option<u32>,
+    limit: Option<i64>,
+) -> Result<Vec<ClipboardItem>, String> {
+    let limit = limit.unwrap_or(50).clamp(1, 500) as usize;
+    let threshold = threshold
+        .or(settings::current().image_duplicate_threshold)
+        .unwrap_or(phash::DEFAULT_THRESHOLD)
+        .min(64);
+    let hashes = db::list_image_hashes(&state.db, None)
+        .await
+        .map_err(|err| err.to_string())?;
+    let target = match hashes.iter().find(|(item_id, _)| *item_id == id) {
+        Some((_, hash)) => *hash as u64,
+        // 后台还没来得及补算指纹的旧图片，这里直接计算
+        None => {
+            let row = db::get_item(&state.db, id)
+                .await
+                .map_err(|err| err.to_string())?
+                .ok_or_else(|| "记录不存在".to_string())?;
+            let (Some(bytes), Some(width), Some(height)) =
+                (row.image, row.image_width, row.image_height)
+            else {
+                return Err("该记录不是图片".to_string());
+            };
+            let hash = phash::dhash(&bytes, width as u32, height as u32)
+                .ok_or_else(|| "图片数据无效".to_string())?;
+            db::save_image_hash(&state.db, id, hash as i64)
+                .await
+                .map_err(|err| err.to_string())?;
+            hash
+        }
+    };
+
+    let mut matches: Vec<(i64, u32)> = hashes
+        .into_iter()
+        .filter(|(item_id, _)| *item_id != id)
+        .map(|(item_id, hash)| (item_id, phash::distance(target, hash as u64)))
+        .filter(|(_, distance)| *distance <= threshold)
+        .collect();
+    matches.sort_by_key(|(_, distance)| *distance);
+    matches.truncate(limit);
+    let ids: Vec<i64> = matches.into_iter().map(|(item_id, _)| item_id).collect();
+    let mut rows = db::get_items(&state.db, &ids)
+        .await
+        .map_err(|err| err.to_string())?;
+    rows.sort_by_key(|row| ids.iter().position(|item_id| *item_id == row.id));
+    Ok(rows.into_iter().map(ClipboardItem::from_row).collect())
+}
+
+/// 按顺序对图片执行编辑操作，结果作为一条新的历史记录保存
+#[tauri::command]
+async fn edit_image(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    operations: Vec<ImageOp>,
+) -> Result<ClipboardItem, String> {
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    let (Some(bytes), Some(width), Some(height)) = (row.image, row.image_width, row.image_height)
+    else {
+        return Err("该记录不是图片".to_string());
+    };
+    let edited = tauri::async_runtime::spawn_blocking(move || {
+        let image = image::RgbaImage::from_raw(width as u32, height as u32, bytes)
+            .ok_or_else(|| "图片数据无效".to_string())?;
+        image_ops::apply_all(image, &operations)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let (width, height) = edited.dimensions();
+    let bytes = edited.into_raw();
+    let phash = phash::dhash(&bytes, width, height);
+    let mut item = NewClipboardItem::image(
+        bytes,
+        width as i64,
+        height as i64,
+        phash,
+        clipboard::now_ms(),
+    );
+    item.source_app = row.source_app;
+    item.custom_category = row.custom_category;
+    item.tags = row.tags;
+    item.metadata = Some(serde_json::json!({ "editedFrom": id }).to_string());
+    let new_id = db::insert_item(&state.db, item)
+        .await
+        .map_err(|err| err.to_string())?;
+    ocr::wake();
+    clipboard::emit_updated(&app);
+    let row = db::get_item(&state.db, new_id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    Ok(ClipboardItem::from_row(row))
+}
+
+/// 把图片记录保存为文件；未指定格式时按扩展名推断
+#[tauri::command]
+async fn save_image(
+    state: State<'_, AppState>,
+    id: i64,
+    path: String,
+    format: Option<ImageFileFormat>,
+    quality: Option<u8>,
+) -> Result<(), String> {
+    let path = PathBuf::from(path.trim());
+    let format = format
+        .or_else(|| ImageFileFormat::from_path(&path))
+        .ok_or_else(|| "无法从文件名判断图片格式".to_string())?;
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    let image = row.rgba_image().ok_or_else(|| "该记录不是图片".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = image_ops::encode(image, format, quality)?;
+        std::fs::write(&path, bytes).map_err(|err| format!("写入文件失败: {err}"))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// 导出时间范围内的全部图片，文件名由时间戳和 id 组成，重复导出会覆盖同名文件
+#[tauri::command]
+async fn export_images(
+    state: State<'_, AppState>,
+    start_ts: i64,
+    end_ts: i64,
+    directory: String,
+    format: ImageFileFormat,
+    quality: Option<u8>,
+) -> Result<ImageExportSummary, String> {
+    let directory = PathBuf::from(directory.trim());
+    std::fs::create_dir_all(&directory).map_err(|err| format!("创建目录失败: {err}"))?;
+    let ids = db::list_image_ids_by_date_range(&state.db, start_ts, end_ts)
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut files = Vec::new();
+    for id in ids {
+        let Some(row) = db::get_item(&state.db, id)
+            .await
+            .map_err(|err| err.to_string())?
+        else {
+            continue;
+        };
+        let Some(image) = row.rgba_image() else {
+            continue;
+        };
+        let name = format!("clipboard-{}-{}.{}", row.created_at, id, format.extension());
+        let path = directory.join(&name);
+        tauri::async_runtime::spawn_blocking(move || {
+            let bytes = image_ops::encode(image, format, quality)?;
+            std::fs::write(&path, bytes).map_err(|err| format!("写入文件失败: {err}"))
+        })
+        .await
+        .map_err(|err| err.to_string())??;
+        files.push(name);
+    }
+    Ok(ImageExportSummary {
+        directory: directory.to_string_lossy().into_owned(),
+        files,
+    })
+}
+
+/// 把磁盘上的图片文件导入历史，重复内容返回已有记录
+#[tauri::command]
+async fn import_image_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<ClipboardItem, String> {
+    let path = PathBuf::from(path.trim());
+    let id = clipboard::import_image_file(&app, &state.db, &path).await?;
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    Ok(ClipboardItem::from_row(row))
+}
+
+#[tauri::command]
+fn ocr_available() -> bool {
+    ocr::available()
+}
+
+#[tauri::command]
+fn reveal_path(path: String) -> Result<(), String> {
+    paths::reveal_in_folder(path.trim())
+}
+
+#[tauri::command]
+async fn set_clipboard(
+    state: State<'_, AppState>,
+    id: i64,
+    mode: Option<PasteMode>,
+) -> Result<(), String> {
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+
+    write_to_clipboard(row, resolve_paste_mode(mode)).map_err(|err| err.to_string())
+}
+
+/// 对记录的文本依次执行转换后写入剪贴板；save 为 true 时同时保存为新记录
+#[tauri::command]
+async fn set_clipboard_transformed(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    transforms: Vec<Transform>,
+    save: Option<bool>,
+) -> Result<String, String> {
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    let text = paste::render_text(&row, PasteMode::PlainText)
+        .ok_or_else(|| "该记录没有文本内容".to_string())?;
+    let transformed = transforms::apply_all(&text, &transforms)?;
+
+    // 先保存再写剪贴板，监听到这次写入时只更新保存的记录（不保存时为原记录）
+    let mut saved_id = None;
+    if save.unwrap_or(false) {
+        let mut extra = serde_json::Map::new();
+        extra.insert("transformedFrom".to_string(), id.into());
+        extra.insert(
+            "transforms".to_string(),
+            serde_json::to_value(&transforms).map_err(|err| err.to_string())?,
+        );
+        saved_id = Some(clipboard::save_text(&app, &state.db, &transformed, extra).await?);
+    }
+    write_text(&transformed, saved_id.or(Some(id)))?;
+    Ok(transformed)
+}
+
+/// 按选择顺序合并多条记录的文本后写入剪贴板；save 为 true 时同时保存为新记录
+#[tauri::command]
+async fn set_clipboard_merged(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    separator: Option<MergeSeparator>,
+    template: Option<String>,
+    save: Option<bool>,
+) -> Result<String, String> {
+    if ids.is_empty() {
+        return Err("没有选择记录".to_string());
+    }
+    let mut texts = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let row = db::get_item(&state.db, *id)
+            .await
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("记录不存在: {id}"))?;
+        let text = paste::render_text(&row, PasteMode::PlainText)
+            .ok_or_else(|| format!("记录 {id} 没有文本内容"))?;
+        texts.push(text);
+    }
+    let separator = separator.unwrap_or_default();
+    let merged = paste::merge_texts(&texts, &separator, template.as_deref());
+
+    let mut saved_id = None;
+    if save.unwrap_or(false) {
+        let mut extra = serde_json::Map::new();
+        extra.insert("mergedFrom".to_string(), ids.into());
+        saved_id = Some(clipboard::save_text(&app, &state.db, &merged, extra).await?);
+    }
+    write_text(&merged, saved_id)?;
+    Ok(merged)
+}
+
+#[tauri::command]
+async fn copy_color_as(
+    state: State<'_, AppState>,
+    id: i64,
+    notation: ColorNotation,
+) -> Result<String, String> {
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    // color 字段已经归一化成 #rrggbbaa，不能再按来源程序解析
+    let parsed = match row.color.as_deref() {
+        Some(color) => color::parse_color(color, None),
+        None => row
+            .text
+            .as_deref()
+            .and_then(|text| color::parse_color(text, row.source_app.as_deref())),
+    }
+    .ok_or_else(|| "该记录不是颜色".to_string())?;
+
+    let text = parsed.format(notation);
+    write_text(&text, Some(id))?;
+    Ok(text)
+}
+
+#[tauri::command]
+async fn copy_structured_as(
+    state: State<'_, AppState>,
+    id: i64,
+    output: StructuredOutput,
+) -> Result<String, String> {
     let row = db::get_item(&state.db, id)
         .await
         .map_err(|err| err.to_string())?
         .ok_or_else(|| "记录不存在".to_string())?;
+    let (Some(text), Some(format)) = (row.text, row.data_format) else {
+        return Err("该记录不是结构化数据".to_string());
+    };
 
-    write_to_clipboard(row).map_err(|err| err.to_string())
+    let converted = structured::convert(&text, &format, output)?;
+    write_text(&converted, Some(id))?;
+    Ok(converted)
 }
 
 #[tauri::command]
@@ -112,94 +576,413 @@ async fn clear_history(state: State<'_, AppState>) -> Result<(), String> {
     db::clear_all(&state.db).await.map_err(|err| err.to_string())
 }
 
-#[cfg(target_os = "windows")]
+/// 粘贴历史记录；传 snippet_id 时改为展开片段的占位符后粘贴。两者都按目标程序的粘贴配置处理
 #[tauri::command]
-async fn set_clipboard_and_paste(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    use windows::Win32::UI::Input::KeyboardAndMouse::{
-        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_CONTROL, VK_V,
+async fn set_clipboard_and_paste(
+    state: State<'_, AppState>,
+    id: Option<i64>,
+    snippet_id: Option<i64>,
+    inputs: Option<HashMap<String, String>>,
+    mode: Option<PasteMode>,
+    restore: Option<bool>,
+) -> Result<(), String> {
+    let content = match (snippet_id, id) {
+        (Some(snippet_id), _) => PasteContent::Text(
+            expand_snippet_by_id(&state.db, snippet_id, inputs.unwrap_or_default()).await?,
+        ),
+        (None, Some(id)) => PasteContent::Item(
+            db::get_item(&state.db, id)
+                .await
+                .map_err(|err| err.to_string())?
+                .ok_or_else(|| "记录不存在".to_string())?,
+        ),
+        (None, None) => return Err("没有指定要粘贴的记录".to_string()),
     };
 
-    // 先写入剪贴板
-    let row = db::get_item(&state.db, id)
+    // 按呼出弹窗时的前台程序选择粘贴配置，调用方指定的 mode 优先
+    let settings = settings::current();
+    let profile = paste_profile(&settings, paste_input::target_app());
+    let mode = mode
+        .or(profile.and_then(|profile| profile.paste_mode))
+        .unwrap_or(settings.default_paste_mode);
+    let chord = profile
+        .and_then(|profile| profile.paste_chord)
+        .unwrap_or(settings.paste_chord);
+
+    // 切回呼出弹窗前的窗口
+    paste_input::restore_focus();
+    paste_content(&settings, content, profile, mode, chord, restore).await?;
+
+    if let Some(snippet_id) = snippet_id {
+        db::touch_snippet(&state.db, snippet_id, clipboard::now_ms())
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_snippets(
+    state: State<'_, AppState>,
+    query: Option<String>,
+) -> Result<Vec<Snippet>, String> {
+    let rows = db::list_snippets(&state.db, query.as_deref().map(str::trim))
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(rows.into_iter().map(Snippet::from_row).collect())
+}
+
+#[tauri::command]
+async fn create_snippet(
+    state: State<'_, AppState>,
+    snippet: SnippetInput,
+) -> Result<Snippet, String> {
+    let snippet = validate_snippet(snippet)?;
+    let id = db::insert_snippet(&state.db, &snippet, clipboard::now_ms())
+        .await
+        .map_err(|err| err.to_string())?;
+    load_snippet(&state.db, id).await
+}
+
+#[tauri::command]
+async fn update_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+    snippet: SnippetInput,
+) -> Result<Snippet, String> {
+    let snippet = validate_snippet(snippet)?;
+    let found = db::update_snippet(&state.db, id, &snippet, clipboard::now_ms())
+        .await
+        .map_err(|err| err.to_string())?;
+    if !found {
+        return Err("片段不存在".to_string());
+    }
+    load_snippet(&state.db, id).await
+}
+
+#[tauri::command]
+async fn delete_snippet(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    db::delete_snippet(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 只展开不粘贴，前端用来预览
+#[tauri::command]
+async fn expand_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+    inputs: Option<HashMap<String, String>>,
+) -> Result<Expansion, String> {
+    expand_snippet_by_id(&state.db, id, inputs.unwrap_or_default()).await
+}
+
+fn validate_snippet(mut snippet: SnippetInput) -> Result<SnippetInput, String> {
+    snippet.name = snippet.name.trim().to_string();
+    if snippet.name.is_empty() {
+        return Err("片段名称不能为空".to_string());
+    }
+    snippet.tags = snippet
+        .tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    Ok(snippet)
+}
+
+async fn load_snippet(pool: &SqlitePool, id: i64) -> Result<Snippet, String> {
+    db::get_snippet(pool, id)
         .await
         .map_err(|err| err.to_string())?
-        .ok_or_else(|| "记录不存在".to_string())?;
+        .map(Snippet::from_row)
+        .ok_or_else(|| "片段不存在".to_string())
+}
+
+async fn expand_snippet_by_id(
+    pool: &SqlitePool,
+    id: i64,
+    inputs: HashMap<String, String>,
+) -> Result<Expansion, String> {
+    let snippet = load_snippet(pool, id).await?;
+    expand_template(&snippet.content, &inputs)
+}
+
+fn expand_template(template: &str, inputs: &HashMap<String, String>) -> Result<Expansion, String> {
+    // {{clipboard}} 取展开时剪贴板里的文本
+    let clipboard = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok();
+    snippets::expand(
+        template,
+        &ExpandContext {
+            now: chrono::Local::now(),
+            clipboard: clipboard.as_deref(),
+            inputs,
+        },
+    )
+}
+
+/// 删掉刚输入的缩写，再把替换内容粘贴到当前窗口
+async fn expand_abbreviation(pool: &SqlitePool, abbreviation: Abbreviation) -> Result<(), String> {
+    // 先准备好内容，出错时保留用户输入的缩写
+    let content = match abbreviation.target {
+        AbbreviationTarget::Text { ref text } => {
+            PasteContent::Text(expand_template(text, &HashMap::new())?)
+        }
+        AbbreviationTarget::Snippet { id } => {
+            PasteContent::Text(expand_snippet_by_id(pool, id, HashMap::new()).await?)
+        }
+        AbbreviationTarget::Item { id } => PasteContent::Item(
+            db::get_item(pool, id)
+                .await
+                .map_err(|err| err.to_string())?
+                .ok_or_else(|| "记录不存在".to_string())?,
+        ),
+    };
 
-    write_to_clipboard(row).map_err(|err| err.to_string())?;
+    // 缩写是在当前窗口里输入的，按当前前台程序选择粘贴配置
+    let settings = settings::current();
+    let profile = paste_profile(&settings, foreground::foreground_app());
+    let mode = profile
+        .and_then(|profile| profile.paste_mode)
+        .unwrap_or(settings.default_paste_mode);
+    let chord = profile
+        .and_then(|profile| profile.paste_chord)
+        .unwrap_or(settings.paste_chord);
+
+    // 等缩写的最后一个按键送达目标程序再删除
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    paste_input::delete_backward(abbreviation.trigger.chars().count())?;
+
+    paste_content(&settings, content, profile, mode, chord, None).await?;
+
+    if let AbbreviationTarget::Snippet { id } = abbreviation.target {
+        db::touch_snippet(pool, id, clipboard::now_ms())
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+// 要粘贴的内容：展开后的片段文本，或者历史记录
+enum PasteContent {
+    Text(Expansion),
+    Item(ClipboardItemRow),
+}
+
+// 按粘贴配置写入并粘贴，片段里有 {{cursor}} 时把光标移过去
+async fn paste_content(
+    settings: &Settings,
+    content: PasteContent,
+    profile: Option<&PasteProfile>,
+    mode: PasteMode,
+    chord: PasteChord,
+    restore: Option<bool>,
+) -> Result<(), String> {
+    let content = match content {
+        PasteContent::Text(expansion) => {
+            PasteContent::Text(transform_expansion(expansion, profile)?)
+        }
+        item => item,
+    };
+    let cursor_offset = match &content {
+        PasteContent::Text(expansion) => expansion.cursor_offset,
+        PasteContent::Item(_) => None,
+    };
+    paste_into_target(settings, chord, restore, cursor_offset, || match content {
+        PasteContent::Text(expansion) => write_text(&expansion.text, None),
+        PasteContent::Item(row) => write_item(row, mode, profile),
+    })
+    .await
+}
+
+// 片段展开后是纯文本，粘贴模式对它没有影响，只需要套用配置里的转换。
+// 排序、编码之类的转换会打乱 {{cursor}} 的位置，转换过的文本不再移动光标
+fn transform_expansion(
+    expansion: Expansion,
+    profile: Option<&PasteProfile>,
+) -> Result<Expansion, String> {
+    match profile
+        .map(|profile| &profile.transforms)
+        .filter(|transforms| !transforms.is_empty())
+    {
+        Some(transforms) => Ok(Expansion {
+            text: transforms::apply_all(&expansion.text, transforms)?,
+            cursor_offset: None,
+        }),
+        None => Ok(expansion),
+    }
+}
+
+// 按目标程序选择粘贴配置
+fn paste_profile(settings: &Settings, app: Option<String>) -> Option<&PasteProfile> {
+    let app = app?;
+    let profile = paste_profiles::find(settings, Some(&app))?;
+    clipboard::log_line(&format!("paste: using profile {} for {app}", profile.name));
+    Some(profile)
+}
+
+// 按配置写回历史记录；配置里有转换时写入转换后的文本，图片等没有文本的记录原样写入
+fn write_item(
+    row: ClipboardItemRow,
+    mode: PasteMode,
+    profile: Option<&PasteProfile>,
+) -> Result<(), String> {
+    let transforms = profile
+        .map(|profile| &profile.transforms)
+        .filter(|transforms| !transforms.is_empty());
+    if let Some(transforms) = transforms {
+        if let Some(text) = paste::render_text(&row, text_paste_mode(mode)) {
+            let text = transforms::apply_all(&text, transforms)?;
+            return write_text(&text, Some(row.id));
+        }
+    }
+    write_to_clipboard(row, mode).map_err(|err| err.to_string())
+}
+
+// 写入剪贴板后向当前窗口模拟粘贴；write 在保存原剪贴板内容之后执行
+async fn paste_into_target(
+    settings: &Settings,
+    chord: PasteChord,
+    restore: Option<bool>,
+    cursor_offset: Option<usize>,
+    write: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    // 需要恢复时先保存原内容；写入和恢复的内容都会标记为自己写入，不会新增记录
+    let restore_delay = std::time::Duration::from_millis(
+        settings
+            .restore_clipboard_delay_ms
+            .unwrap_or(clipboard::DEFAULT_RESTORE_DELAY_MS),
+    );
+    let restore = restore.unwrap_or(settings.restore_clipboard_after_paste);
+    let generation = restore.then(clipboard::snapshot_for_restore);
+
+    write()?;
 
     // 等待剪贴板写入完成
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-    // 模拟 Ctrl+V 按键
-    unsafe {
-        let inputs = [
-            // 按下 Ctrl
-            INPUT {
-                r#type: INPUT_KEYBOARD,
-                Anonymous: INPUT_0 {
-                    ki: KEYBDINPUT {
-                        wVk: VK_CONTROL,
-                        wScan: 0,
-                        dwFlags: Default::default(),
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-            // 按下 V
-            INPUT {
-                r#type: INPUT_KEYBOARD,
-                Anonymous: INPUT_0 {
-                    ki: KEYBDINPUT {
-                        wVk: VK_V,
-                        wScan: 0,
-                        dwFlags: Default::default(),
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-            // 释放 V
-            INPUT {
-                r#type: INPUT_KEYBOARD,
-                Anonymous: INPUT_0 {
-                    ki: KEYBDINPUT {
-                        wVk: VK_V,
-                        wScan: 0,
-                        dwFlags: KEYEVENTF_KEYUP,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-            // 释放 Ctrl
-            INPUT {
-                r#type: INPUT_KEYBOARD,
-                Anonymous: INPUT_0 {
-                    ki: KEYBDINPUT {
-                        wVk: VK_CONTROL,
-                        wScan: 0,
-                        dwFlags: KEYEVENTF_KEYUP,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-        ];
-
-        let result = SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
-        if result == 0 {
-            return Err("模拟按键失败".to_string());
-        }
+    paste_input::send_paste(chord)?;
+
+    if let Some(count) = cursor_offset.filter(|count| *count > 0) {
+        // 等目标程序处理完粘贴再移动光标
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        paste_input::move_cursor_left(count)?;
+    }
+
+    if let Some(generation) = generation {
+        clipboard::schedule_restore(generation, restore_delay);
     }
 
     Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
+/// 呼出弹窗时的前台程序，前端用来为当前程序创建粘贴配置
 #[tauri::command]
-async fn set_clipboard_and_paste(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    set_clipboard(state, id).await
+fn get_paste_target_app() -> Option<String> {
+    paste_input::target_app()
+}
+
+/// 逐字模拟按键输入记录的文本，用于不允许粘贴的输入框和远程控制台
+#[tauri::command]
+async fn type_clipboard_item(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    mode: Option<PasteMode>,
+    delay_ms: Option<u64>,
+) -> Result<(), String> {
+    let row = db::get_item(&state.db, id)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| "记录不存在".to_string())?;
+    let mode = text_paste_mode(resolve_paste_mode(mode));
+    let text = paste::render_text(&row, mode).ok_or_else(|| "图片无法逐字输入".to_string())?;
+    let delay = std::time::Duration::from_millis(
+        delay_ms
+            .or(settings::current().type_delay_ms)
+            .unwrap_or(paste_input::DEFAULT_TYPE_DELAY_MS),
+    );
+
+    paste_input::restore_focus();
+    // 输入期间不要把自己模拟的按键当成缩写
+    abbreviations::suspend(delay * text.chars().count() as u32 + std::time::Duration::from_secs(1));
+    // 长文本要输入很久，放到阻塞线程里执行
+    tauri::async_runtime::spawn_blocking(move || paste_input::type_text(&text, delay))
+        .await
+        .map_err(|err| err.to_string())??;
+
+    db::touch_item(&state.db, id, clipboard::now_ms())
+        .await
+        .map_err(|err| err.to_string())?;
+    clipboard::emit_updated(&app);
+    Ok(())
+}
+
+/// 用选中的记录开始连续粘贴，之后每次按 Ctrl+V 粘贴队列里的下一条
+#[tauri::command]
+async fn start_paste_queue(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    order: Option<QueueOrder>,
+) -> Result<QueueStatus, String> {
+    let mut rows = db::get_items(&state.db, &ids)
+        .await
+        .map_err(|err| err.to_string())?;
+    // 按选择的顺序入队
+    rows.sort_by_key(|row| ids.iter().position(|id| *id == row.id));
+    paste_queue::start(&app, rows, order.unwrap_or_default());
+    // 先把第一条写入剪贴板，按下 Ctrl+V 时直接粘贴
+    prefetch_paste_queue(None)?;
+    Ok(paste_queue::status())
+}
+
+/// 清空队列并开始收集之后复制的内容
+#[tauri::command]
+fn collect_paste_queue(app: tauri::AppHandle, order: Option<QueueOrder>) -> QueueStatus {
+    paste_queue::start_collecting(&app, order.unwrap_or_default());
+    paste_queue::status()
+}
+
+#[tauri::command]
+fn clear_paste_queue(app: tauri::AppHandle) {
+    paste_queue::clear(&app);
+}
+
+#[tauri::command]
+fn get_paste_queue_status() -> QueueStatus {
+    paste_queue::status()
+}
+
+/// 粘贴完剪贴板里的这一条后调用：出队并把下一条写入剪贴板，返回剪贴板里记录的 id；
+/// 队列为空时返回 None。Windows 上由 Ctrl+V 拦截自动完成，其他平台需要手动调用
+#[tauri::command]
+fn paste_queue_next(app: tauri::AppHandle, mode: Option<PasteMode>) -> Result<Option<i64>, String> {
+    advance_paste_queue(&app, mode)
+}
+
+// 队首已经在剪贴板里（已被粘贴）时出队，再把新的队首写入剪贴板
+fn advance_paste_queue(
+    app: &tauri::AppHandle,
+    mode: Option<PasteMode>,
+) -> Result<Option<i64>, String> {
+    if paste_queue::head_prefetched() == Some(true) {
+        paste_queue::pop(app);
+    }
+    prefetch_paste_queue(mode)
+}
+
+// 把队首写入剪贴板但不出队，返回它的 id
+fn prefetch_paste_queue(mode: Option<PasteMode>) -> Result<Option<i64>, String> {
+    let Some(row) = paste_queue::peek() else {
+        return Ok(None);
+    };
+    let id = row.id;
+    write_to_clipboard(row, resolve_paste_mode(mode)).map_err(|err| err.to_string())?;
+    paste_queue::mark_prefetched(id);
+    Ok(Some(id))
 }
 
 #[tauri::command]
@@ -254,6 +1037,22 @@ async fn get_cursor_position() -> Result<(i32, i32), String> {
     Ok((0, 0))
 }
 
+#[tauri::command]
+async fn get_settings() -> Result<Settings, String> {
+    Ok(settings::current().as_ref().clone())
+}
+
+#[tauri::command]
+async fn update_settings(settings: Settings) -> Result<(), String> {
+    settings::save(settings)
+}
+
+#[tauri::command]
+async fn list_classifiers() -> Result<Vec<ClassifierInfo>, String> {
+    let settings = settings::current();
+    Ok(classify::pipeline().describe(&settings.disabled_classifiers))
+}
+
 #[tauri::command]
 async fn get_hotkey(app: tauri::AppHandle) -> Result<String, String> {
     // 从配置文件读取快捷键，如果不存在则返回默认值
@@ -283,6 +1082,7 @@ async fn set_hotkey(app: tauri::AppHandle, hotkey: String) -> Result<(), String>
         let app_handle = app.clone();
 
         keyboard_hook::register_hotkey(key_code, ctrl, alt, shift, win, move || {
+            paste_input::remember_focus();
             let handle = app_handle.clone();
             tauri::async_runtime::spawn(async move {
                 if let Some(window) = handle.get_webview_window("popup") {
@@ -353,13 +1153,38 @@ async fn set_hotkey(app: tauri::AppHandle, hotkey: String) -> Result<(), String>
     Ok(())
 }
 
-fn write_to_clipboard(row: ClipboardItemRow) -> Result<(), arboard::Error> {
+fn resolve_paste_mode(mode: Option<PasteMode>) -> PasteMode {
+    mode.unwrap_or_else(|| settings::current().default_paste_mode)
+}
+
+// 只能写入文本时（逐字输入、粘贴前转换），原格式按纯文本处理
+fn text_paste_mode(mode: PasteMode) -> PasteMode {
+    match mode {
+        PasteMode::Original => PasteMode::PlainText,
+        mode => mode,
+    }
+}
+
+/// 写入由记录生成的文本；监听到这次写入时只更新 source_id 的使用时间，不新增记录
+fn write_text(text: &str, source_id: Option<i64>) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|err| err.to_string())?;
+    clipboard::mark_text_written(text, source_id);
+    clipboard.set_text(text).map_err(|err| err.to_string())
+}
+
+fn write_to_clipboard(row: ClipboardItemRow, mode: PasteMode) -> Result<(), arboard::Error> {
     let mut clipboard = arboard::Clipboard::new()?;
-    match row.format.as_str() {
-        "image" => {
+    let id = Some(row.id);
+    if let Some(text) = paste::render_text(&row, mode) {
+        clipboard::mark_text_written(&text, id);
+        return clipboard.set_text(text);
+    }
+    match row.format {
+        ClipboardFormat::Image => {
             if let (Some(bytes), Some(width), Some(height)) =
                 (row.image, row.image_width, row.image_height)
             {
+                clipboard::mark_image_written(&bytes, width as usize, height as usize, id);
                 let data = ImageData {
                     width: width as usize,
                     height: height as usize,
@@ -368,20 +1193,22 @@ fn write_to_clipboard(row: ClipboardItemRow) -> Result<(), arboard::Error> {
                 clipboard.set_image(data)?;
             }
         }
-        "html" => {
+        ClipboardFormat::Html => {
             if let Some(html) = row.html {
-                clipboard.set_html(html, None)?;
+                // 同时写入纯文本，监听线程读到的是这份文本
+                if let Some(text) = &row.text {
+                    clipboard::mark_text_written(text, id);
+                }
+                clipboard.set_html(html, row.text)?;
             } else if let Some(text) = row.text {
+                clipboard::mark_text_written(&text, id);
                 clipboard.set_text(text)?;
             }
         }
-        _ => {
-            if let Some(text) = row.text {
+        ClipboardFormat::Text | ClipboardFormat::File | ClipboardFormat::Color => {
+            if let Some(text) = row.text.or(row.file_path).or(row.color) {
+                clipboard::mark_text_written(&text, id);
                 clipboard.set_text(text)?;
-            } else if let Some(file_path) = row.file_path {
-                clipboard.set_text(file_path)?;
-            } else if let Some(color) = row.color {
-                clipboard.set_text(color)?;
             }
         }
     }
@@ -398,10 +1225,66 @@ fn main() {
             clipboard::init_logger(log_path);
             let db_path = app_data_dir.join("clipboard.db");
             let pool = tauri::async_runtime::block_on(db::init_db(&db_path))?;
+            let config_dir = app.path().app_config_dir()?;
+            std::fs::create_dir_all(&config_dir)?;
+            settings::init(config_dir.join("settings.json"));
             app.manage(AppState { db: pool.clone() });
             let handle = app.handle().clone();
+            ocr::start_worker(pool.clone());
+            phash::start_backfill(pool.clone());
             clipboard::start_watcher(handle.clone(), pool);
 
+            // 连续粘贴（仅 Windows）：队首预先写入剪贴板，钩子只负责通知，
+            // 粘贴完成后由后台线程出队并写入下一条
+            #[cfg(target_os = "windows")]
+            {
+                let (paste_sender, paste_receiver) = std::sync::mpsc::channel::<bool>();
+                keyboard_hook::set_paste_interceptor(move |repeat| {
+                    let Some(prefetched) = paste_queue::head_prefetched() else {
+                        return false;
+                    };
+                    // 按住不放的重复按键会连续出队，直接吞掉
+                    if repeat {
+                        return true;
+                    }
+                    let _ = paste_sender.send(prefetched);
+                    // 队首还不在剪贴板里时先吞掉这次按键，写入后再补发
+                    !prefetched
+                });
+                let queue_handle = handle.clone();
+                std::thread::spawn(move || {
+                    for prefetched in paste_receiver {
+                        if !prefetched {
+                            let result = prefetch_paste_queue(None).and_then(|_| {
+                                std::thread::sleep(std::time::Duration::from_millis(100));
+                                paste_input::send_paste(PasteChord::CtrlV)
+                            });
+                            if let Err(err) = result {
+                                clipboard::log_line(&format!("paste queue: paste failed: {err}"));
+                                continue;
+                            }
+                        }
+                        // 等目标程序读完剪贴板再换成下一条
+                        std::thread::sleep(std::time::Duration::from_millis(300));
+                        if let Err(err) = advance_paste_queue(&queue_handle, None) {
+                            clipboard::log_line(&format!("paste queue: write failed: {err}"));
+                        }
+                    }
+                });
+            }
+
+            // 输入缩写后删掉缩写并粘贴对应的内容
+            let abbreviation_pool = app.state::<AppState>().db.clone();
+            abbreviations::set_handler(move |abbreviation| {
+                let pool = abbreviation_pool.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) = expand_abbreviation(&pool, abbreviation).await {
+                        clipboard::log_line(&format!("abbreviation: expand failed: {err}"));
+                    }
+                });
+            });
+            keyboard_hook::start_abbreviation_listener();
+
             // 首次运行时，默认开启自启动
             let first_run_flag = app_data_dir.join(".first_run");
             if !first_run_flag.exists() {
@@ -500,8 +1383,6 @@ fn main() {
             let app_handle = app.handle().clone();
 
             // 读取保存的快捷键配置
-            let config_dir = app.path().app_config_dir()?;
-            std::fs::create_dir_all(&config_dir)?;
             let config_file = config_dir.join("hotkey.txt");
             let hotkey_str = if config_file.exists() {
                 std::fs::read_to_string(config_file).unwrap_or("Alt+V".to_string())
@@ -515,6 +1396,7 @@ fn main() {
             #[cfg(target_os = "windows")]
             {
                 keyboard_hook::register_hotkey(key_code, ctrl, alt, shift, win, move || {
+                    paste_input::remember_focus();
                     let handle = app_handle.clone();
                     tauri::async_runtime::spawn(async move {
                         if let Some(window) = handle.get_webview_window("popup") {
@@ -603,7 +1485,7 @@ fn main() {
                 // 非 Windows 系统使用原来的方式
                 tauri::async_runtime::spawn(async move {
                     use tauri_plugin_global_shortcut::{
-                        Code, GlobalShortcutExt, Modifiers, Shortcut,
+                        Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState,
                     };
 
                     let shortcut =
@@ -611,8 +1493,16 @@ fn main() {
 
                     let _ = app_handle.global_shortcut().on_shortcut(
                         shortcut,
-                        move |app, _shortcut, _event| {
-                            // ... 原来的逻辑
+                        move |app, _shortcut, event| {
+                            if event.state() != ShortcutState::Pressed {
+                                return;
+                            }
+                            // 记下当前窗口，粘贴前切回去
+                            paste_input::remember_focus();
+                            if let Some(window) = app.get_webview_window("popup") {
+                                let _ = window.show();
+                                let _ = window.set_focus();
+                            }
                         },
                     );
                 });
@@ -625,12 +1515,46 @@ fn main() {
             search_history,
             set_clipboard,
             set_clipboard_and_paste,
+            type_clipboard_item,
+            get_paste_target_app,
+            list_snippets,
+            create_snippet,
+            update_snippet,
+            delete_snippet,
+            expand_snippet,
             clear_history,
             list_history_by_date,
             search_history_by_date,
             get_cursor_position,
             get_hotkey,
-            set_hotkey
+            set_hotkey,
+            list_link_hosts,
+            list_history_by_link_host,
+            copy_color_as,
+            list_languages,
+            list_history_by_language,
+            copy_structured_as,
+            get_settings,
+            update_settings,
+            list_classifiers,
+            list_entity_kinds,
+            list_item_entities,
+            list_history_by_entity,
+            list_item_paths,
+            reveal_path,
+            ocr_available,
+            find_similar_images,
+            edit_image,
+            save_image,
+            export_images,
+            import_image_file,
+            set_clipboard_transformed,
+            start_paste_queue,
+            collect_paste_queue,
+            clear_paste_queue,
+            get_paste_queue_status,
+            paste_queue_next,
+            set_clipboard_merged
         ])
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .run(tauri::generate_context!())
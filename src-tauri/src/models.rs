@@ -1,57 +1,372 @@
 use std::io::Cursor;
 
 use base64::Engine;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use ts_rs::TS;
+
+/// 剪贴板内容的存储格式，决定重新写回剪贴板时使用哪种数据
+///
+/// 取值会写进数据库的 CHECK 约束，新增取值时需要在 db 中增加一次重建表的迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, TS)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/bindings/")]
+pub enum ClipboardFormat {
+    Text,
+    Image,
+    Html,
+    File,
+    Color,
+}
+
+/// 界面上按类别筛选用的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, TS)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/bindings/")]
+pub enum ClipboardCategory {
+    Link,
+    Image,
+    Text,
+    File,
+    Code,
+    Data,
+}
+
+/// 从文本中提取的实体类型，存放在 item_entities 表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, TS)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/bindings/")]
+pub enum EntityKind {
+    Email,
+    Phone,
+    Address,
+}
+
+/// 路径检查时的类型，不存在的路径为空
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, TS)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/bindings/")]
+pub enum PathKind {
+    File,
+    Directory,
+}
+
+impl ClipboardFormat {
+    pub const ALL: [Self; 5] = [Self::Text, Self::Image, Self::Html, Self::File, Self::Color];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Image => "image",
+            Self::Html => "html",
+            Self::File => "file",
+            Self::Color => "color",
+        }
+    }
+}
+
+impl EntityKind {
+    pub const ALL: [Self; 3] = [Self::Email, Self::Phone, Self::Address];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::Address => "address",
+        }
+    }
+}
+
+impl PathKind {
+    pub const ALL: [Self; 2] = [Self::File, Self::Directory];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Directory => "directory",
+        }
+    }
+}
+
+impl ClipboardCategory {
+    pub const ALL: [Self; 6] = [
+        Self::Link,
+        Self::Image,
+        Self::Text,
+        Self::File,
+        Self::Code,
+        Self::Data,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Link => "link",
+            Self::Image => "image",
+            Self::Text => "text",
+            Self::File => "file",
+            Self::Code => "code",
+            Self::Data => "data",
+        }
+    }
+}
 
 #[derive(Debug, Clone, FromRow)]
 pub struct ClipboardItemRow {
     pub id: i64,
-    pub format: String,
-    pub category: String,
+    pub format: ClipboardFormat,
+    pub category: ClipboardCategory,
     pub text: Option<String>,
     pub html: Option<String>,
     pub file_path: Option<String>,
     pub color: Option<String>,
+    pub link_url: Option<String>,
+    pub link_host: Option<String>,
+    pub link_scheme: Option<String>,
+    pub language: Option<String>,
+    pub data_format: Option<String>,
+    pub data_valid: Option<bool>,
+    pub data_shape: Option<String>,
+    pub source_app: Option<String>,
+    pub custom_category: Option<String>,
+    pub tags: Option<String>,
+    pub metadata: Option<String>,
+    pub ocr_text: Option<String>,
     pub image: Option<Vec<u8>>,
     pub image_width: Option<i64>,
     pub image_height: Option<i64>,
     pub created_at: i64,
+    pub last_used_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClipboardItem {
     pub id: i64,
-    pub format: String,
-    pub category: String,
+    pub format: ClipboardFormat,
+    pub category: ClipboardCategory,
     pub text: Option<String>,
     pub html: Option<String>,
     pub file_path: Option<String>,
     pub color: Option<String>,
+    pub link_url: Option<String>,
+    pub link_host: Option<String>,
+    pub link_scheme: Option<String>,
+    pub language: Option<String>,
+    pub data_format: Option<String>,
+    pub data_valid: Option<bool>,
+    pub data_shape: Option<serde_json::Value>,
+    pub source_app: Option<String>,
+    pub custom_category: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: serde_json::Value,
+    pub ocr_text: Option<String>,
     pub image_base64: Option<String>,
     pub image_width: Option<i64>,
     pub image_height: Option<i64>,
     pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+    pub item_id: i64,
+    pub kind: EntityKind,
+    pub value: String,
+    pub normalized: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityKindCount {
+    pub kind: EntityKind,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewEntity {
+    pub kind: EntityKind,
+    pub value: String,
+    /// 邮箱域名转小写、电话转成 E.164、地址合并空白后的值，用于去重和搜索
+    pub normalized: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PathEntry {
+    pub item_id: i64,
+    pub position: i64,
+    pub path: String,
+    pub expanded: String,
+    #[sqlx(rename = "path_exists")]
+    pub exists: Option<bool>,
+    pub kind: Option<PathKind>,
+    pub size: Option<i64>,
+    pub checked_at: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPathEntry {
+    pub path: String,
+    /// 展开 ~ 和 $HOME 之后的路径
+    pub expanded: String,
+    /// 为空表示还没有检查过（例如网络路径）
+    pub exists: Option<bool>,
+    pub kind: Option<PathKind>,
+    pub size: Option<i64>,
+    pub checked_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageExportSummary {
+    pub directory: String,
+    /// 导出的文件名，位于 directory 下
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageCount {
+    pub language: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkHostCount {
+    pub host: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct SnippetRow {
+    pub id: i64,
+    pub name: String,
+    pub content: String,
+    pub tags: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+/// 片段库中的模板，粘贴时展开 {{date}}、{{clipboard}} 等占位符
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: i64,
+    pub name: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    /// 模板中 {{input:名称}} 的名称，粘贴前需要用户填写
+    pub inputs: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl Snippet {
+    pub fn from_row(row: SnippetRow) -> Self {
+        Self {
+            id: row.id,
+            inputs: crate::snippets::input_names(&row.content),
+            name: row.name,
+            content: row.content,
+            tags: row
+                .tags
+                .and_then(|tags| serde_json::from_str(&tags).ok())
+                .unwrap_or_default(),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            last_used_at: row.last_used_at,
+        }
+    }
+}
+
+/// 新建或修改片段时前端提交的内容
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetInput {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct NewClipboardItem {
-    pub format: String,
-    pub category: String,
+    pub format: ClipboardFormat,
+    pub category: ClipboardCategory,
     pub text: Option<String>,
     pub html: Option<String>,
     pub file_path: Option<String>,
     pub color: Option<String>,
+    pub link_url: Option<String>,
+    pub link_host: Option<String>,
+    pub link_scheme: Option<String>,
+    pub language: Option<String>,
+    pub data_format: Option<String>,
+    pub data_valid: Option<bool>,
+    pub data_shape: Option<String>,
+    pub source_app: Option<String>,
+    pub custom_category: Option<String>,
+    pub tags: Option<String>,
+    pub metadata: Option<String>,
+    /// 图片的 dHash 指纹，按位存成 i64
+    pub image_phash: Option<i64>,
     pub image: Option<Vec<u8>>,
     pub image_width: Option<i64>,
     pub image_height: Option<i64>,
     pub created_at: i64,
+    pub entities: Vec<NewEntity>,
+    pub paths: Vec<NewPathEntry>,
+}
+
+impl NewClipboardItem {
+    /// 图片记录，其余字段为空，由调用方按需补充来源、分类等
+    pub fn image(
+        bytes: Vec<u8>,
+        width: i64,
+        height: i64,
+        phash: Option<u64>,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            format: ClipboardFormat::Image,
+            category: ClipboardCategory::Image,
+            text: None,
+            html: None,
+            file_path: None,
+            color: None,
+            link_url: None,
+            link_host: None,
+            link_scheme: None,
+            language: None,
+            data_format: None,
+            data_valid: None,
+            data_shape: None,
+            source_app: None,
+            custom_category: None,
+            tags: None,
+            metadata: None,
+            image_phash: phash.map(|hash| hash as i64),
+            image: Some(bytes),
+            image_width: Some(width),
+            image_height: Some(height),
+            created_at,
+            entities: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
 }
 
 impl ClipboardItem {
     pub fn from_row(row: ClipboardItemRow) -> Self {
-        let image_base64 = encode_png(&row.image, row.image_width, row.image_height);
+        let image_base64 = row.rgba_image().and_then(encode_png);
         Self {
             id: row.id,
             format: row.format,
@@ -60,19 +375,46 @@ impl ClipboardItem {
             html: row.html,
             file_path: row.file_path,
             color: row.color,
+            link_url: row.link_url,
+            link_host: row.link_host,
+            link_scheme: row.link_scheme,
+            language: row.language,
+            data_format: row.data_format,
+            data_valid: row.data_valid,
+            data_shape: row
+                .data_shape
+                .and_then(|shape| serde_json::from_str(&shape).ok()),
+            source_app: row.source_app,
+            custom_category: row.custom_category,
+            tags: row
+                .tags
+                .and_then(|tags| serde_json::from_str(&tags).ok())
+                .unwrap_or_default(),
+            metadata: row
+                .metadata
+                .and_then(|metadata| serde_json::from_str(&metadata).ok())
+                .unwrap_or_else(|| serde_json::Value::Object(Default::default())),
+            ocr_text: row.ocr_text,
             image_base64,
             image_width: row.image_width,
             image_height: row.image_height,
             created_at: row.created_at,
+            last_used_at: row.last_used_at,
         }
     }
 }
 
-fn encode_png(image: &Option<Vec<u8>>, width: Option<i64>, height: Option<i64>) -> Option<String> {
-    let bytes = image.as_ref()?;
-    let width: u32 = width?.try_into().ok()?;
-    let height: u32 = height?.try_into().ok()?;
-    let rgba = image::RgbaImage::from_raw(width, height, bytes.clone())?;
+impl ClipboardItemRow {
+    /// 把数据库中保存的原始 RGBA 数据还原成图片
+    pub fn rgba_image(&self) -> Option<image::RgbaImage> {
+        let bytes = self.image.as_ref()?;
+        let width: u32 = self.image_width?.try_into().ok()?;
+        let height: u32 = self.image_height?.try_into().ok()?;
+        image::RgbaImage::from_raw(width, height, bytes.clone())
+    }
+}
+
+fn encode_png(rgba: image::RgbaImage) -> Option<String> {
     let dynamic = image::DynamicImage::ImageRgba8(rgba);
     let mut buffer = Vec::new();
     if dynamic
@@ -0,0 +1,113 @@
+use sqlx::SqlitePool;
+
+/// 是否编译了 OCR 支持（cargo build --features ocr，需要本机安装 Tesseract）
+pub fn available() -> bool {
+    cfg!(feature = "ocr")
+}
+
+/// 新图片入库后唤醒后台识别任务
+pub fn wake() {
+    #[cfg(feature = "ocr")]
+    if let Some(notify) = worker::WAKE.get() {
+        notify.notify_one();
+    }
+}
+
+/// 启动后台 OCR 任务，依次识别还没有处理过的图片；未启用 ocr feature 时什么都不做
+pub fn start_worker(pool: SqlitePool) {
+    #[cfg(feature = "ocr")]
+    worker::start(pool);
+    #[cfg(not(feature = "ocr"))]
+    let _ = pool;
+}
+
+#[cfg(feature = "ocr")]
+mod worker {
+    use std::io::Cursor;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    use sqlx::SqlitePool;
+    use tokio::sync::Notify;
+
+    use crate::clipboard::log_line;
+    use crate::{db, settings};
+
+    pub static WAKE: OnceLock<Notify> = OnceLock::new();
+
+    // 没有新图片时的兜底轮询间隔
+    const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn start(pool: SqlitePool) {
+        let notify = WAKE.get_or_init(Notify::new);
+        tauri::async_runtime::spawn(async move {
+            log_line("ocr: worker started");
+            loop {
+                let pending = match db::next_ocr_pending(&pool).await {
+                    Ok(pending) => pending,
+                    Err(err) => {
+                        log_line(&format!("ocr: failed to load pending image: {err}"));
+                        tokio::time::sleep(IDLE_INTERVAL).await;
+                        continue;
+                    }
+                };
+                let Some((id, bytes, width, height)) = pending else {
+                    let _ = tokio::time::timeout(IDLE_INTERVAL, notify.notified()).await;
+                    continue;
+                };
+
+                let language = settings::current()
+                    .ocr_language
+                    .clone()
+                    .unwrap_or_else(|| "eng".to_string());
+                let result = tauri::async_runtime::spawn_blocking(move || {
+                    recognize(bytes, width, height, &language)
+                })
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|result| result);
+                let saved = match result {
+                    Ok(text) => {
+                        let text = text.trim();
+                        let text = (!text.is_empty()).then_some(text);
+                        db::save_ocr_result(&pool, id, text, "done").await
+                    }
+                    Err(err) => {
+                        log_line(&format!("ocr: item {id} failed: {err}"));
+                        db::save_ocr_result(&pool, id, None, "failed").await
+                    }
+                };
+                if let Err(err) = saved {
+                    log_line(&format!("ocr: failed to save result for item {id}: {err}"));
+                    tokio::time::sleep(IDLE_INTERVAL).await;
+                }
+            }
+        });
+    }
+
+    fn recognize(
+        bytes: Vec<u8>,
+        width: i64,
+        height: i64,
+        language: &str,
+    ) -> Result<String, String> {
+        let width = u32::try_from(width).map_err(|err| err.to_string())?;
+        let height = u32::try_from(height).map_err(|err| err.to_string())?;
+        let rgba = image::RgbaImage::from_raw(width, height, bytes)
+            .ok_or_else(|| "图片数据与尺寸不匹配".to_string())?;
+        // 灰度图识别更稳定，也能减小传给 Tesseract 的数据量
+        let gray = image::DynamicImage::ImageRgba8(rgba).into_luma8();
+        let mut png = Vec::new();
+        gray.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|err| err.to_string())?;
+
+        let mut engine = leptess::LepTess::new(None, language)
+            .map_err(|err| format!("Tesseract 初始化失败: {err}"))?;
+        engine
+            .set_image_from_mem(&png)
+            .map_err(|err| format!("Tesseract 读取图片失败: {err}"))?;
+        // 截图没有 DPI 信息，按常见的屏幕分辨率处理
+        engine.set_source_resolution(96);
+        engine.get_utf8_text().map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::OnceLock;
+
+use html2text::render::TrivialDecorator;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ClipboardFormat, ClipboardItemRow};
+
+// 转纯文本时按这个宽度排版，足够宽以免长段落被硬换行
+const PLAIN_TEXT_WIDTH: usize = 10_000;
+
+/// 从历史写回剪贴板时使用的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteMode {
+    /// 按保存时的格式写回
+    #[default]
+    Original,
+    /// 去掉 HTML 样式，只写纯文本。取值为 plain_text，旧版本设置里的 plainText 仍然可以读取
+    #[serde(rename = "plain_text", alias = "plainText")]
+    PlainText,
+    /// 把 HTML 转成 Markdown 文本
+    Markdown,
+}
+
+/// 合并多条记录时使用的分隔符
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeSeparator {
+    #[default]
+    Newline,
+    Comma,
+    Tab,
+    Space,
+    Custom(String),
+}
+
+impl MergeSeparator {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Newline => "\n",
+            Self::Comma => ", ",
+            Self::Tab => "\t",
+            Self::Space => " ",
+            Self::Custom(separator) => separator,
+        }
+    }
+}
+
+/// 把多段文本按模板和分隔符拼在一起；模板里的 {text} 换成内容，{index} 换成从 1 开始的序号
+pub fn merge_texts(texts: &[String], separator: &MergeSeparator, template: Option<&str>) -> String {
+    let template = template.filter(|template| !template.is_empty());
+    texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| match template {
+            Some(template) => template
+                .replace("{index}", &(index + 1).to_string())
+                .replace("{text}", text),
+            None => text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(separator.as_str())
+}
+
+/// 按粘贴模式决定写入剪贴板的文本；返回 None 时按原格式写回
+pub fn render_text(row: &ClipboardItemRow, mode: PasteMode) -> Option<String> {
+    if mode == PasteMode::Original || row.format == ClipboardFormat::Image {
+        return None;
+    }
+    match row.html.as_deref() {
+        Some(html) => Some(match mode {
+            PasteMode::Markdown => html_to_markdown(html),
+            _ => html_to_plain_text(html),
+        }),
+        None => row
+            .text
+            .clone()
+            .or_else(|| row.file_path.clone())
+            .or_else(|| row.color.clone()),
+    }
+}
+
+/// 按块级元素换行、解码实体，去掉脚本和样式
+pub fn html_to_plain_text(html: &str) -> String {
+    let text = html2text::config::with_decorator(TrivialDecorator::new())
+        .no_table_borders()
+        .allow_width_overflow()
+        .string_from_read(html.as_bytes(), PLAIN_TEXT_WIDTH)
+        .unwrap_or_else(|_| crate::classify::strip_html(html));
+    trim_lines(&text)
+}
+
+pub fn html_to_markdown(html: &str) -> String {
+    // html2md 会把 script、style 里的内容当成文本输出
+    let html = hidden_regex().replace_all(html, "");
+    trim_lines(&html2md::parse_html(&html))
+}
+
+fn hidden_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?is)<script\b.*?</script\s*>|<style\b.*?</style\s*>|<!--.*?-->").unwrap()
+    })
+}
+
+// 去掉每行末尾的空白和首尾空行
+fn trim_lines(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_mode_wire_values() {
+        let cases = [
+            (PasteMode::Original, "\"original\""),
+            (PasteMode::PlainText, "\"plain_text\""),
+            (PasteMode::Markdown, "\"markdown\""),
+        ];
+        for (mode, wire) in cases {
+            assert_eq!(serde_json::to_string(&mode).unwrap(), wire);
+            assert_eq!(serde_json::from_str::<PasteMode>(wire).unwrap(), mode);
+        }
+        assert_eq!(
+            serde_json::from_str::<PasteMode>("\"plainText\"").unwrap(),
+            PasteMode::PlainText
+        );
+    }
+}
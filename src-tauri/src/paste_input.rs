@@ -0,0 +1,806 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::foreground;
+
+/// 逐字输入时两个字符之间的默认间隔
+pub const DEFAULT_TYPE_DELAY_MS: u64 = 15;
+
+/// 模拟粘贴时按下的组合键
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteChord {
+    #[default]
+    CtrlV,
+    /// 大多数 Linux 终端使用 Ctrl+Shift+V
+    CtrlShiftV,
+    ShiftInsert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Control,
+    Shift,
+    V,
+    Insert,
+    Left,
+    Backspace,
+}
+
+impl PasteChord {
+    // 按下顺序，松开时倒序
+    fn keys(self) -> &'static [Key] {
+        match self {
+            Self::CtrlV => &[Key::Control, Key::V],
+            Self::CtrlShiftV => &[Key::Control, Key::Shift, Key::V],
+            Self::ShiftInsert => &[Key::Shift, Key::Insert],
+        }
+    }
+}
+
+// 呼出弹窗时的前台程序，用来选择粘贴配置
+static TARGET_APP: Mutex<Option<String>> = Mutex::new(None);
+
+/// 呼出弹窗前记下当前活动窗口，粘贴前用 restore_focus 切回去
+pub fn remember_focus() {
+    *TARGET_APP.lock().unwrap() = foreground::foreground_app();
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    platform::remember_focus();
+}
+
+/// remember_focus 时的前台程序名
+pub fn target_app() -> Option<String> {
+    TARGET_APP.lock().unwrap().clone()
+}
+
+/// 切回 remember_focus 记下的窗口；Wayland 下无法切换，由合成器在弹窗隐藏后处理
+pub fn restore_focus() {
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    platform::restore_focus();
+}
+
+/// 向当前焦点窗口发送粘贴组合键
+pub fn send_paste(chord: PasteChord) -> Result<(), String> {
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    return platform::send_keys(chord.keys(), 1);
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = chord;
+        Err("当前系统不支持模拟粘贴".to_string())
+    }
+}
+
+/// 按 count 次左方向键，粘贴片段后把光标移到 {{cursor}} 的位置
+pub fn move_cursor_left(count: usize) -> Result<(), String> {
+    repeat_key(Key::Left, count)
+}
+
+/// 按 count 次退格键，替换缩写前删掉已经输入的缩写
+pub fn delete_backward(count: usize) -> Result<(), String> {
+    repeat_key(Key::Backspace, count)
+}
+
+fn repeat_key(key: Key, count: usize) -> Result<(), String> {
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    if count > 0 {
+        platform::send_keys(&[key], count)?;
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let _ = (key, count);
+    Ok(())
+}
+
+/// 当前是否是 Wayland 会话，此时只能通过 uinput/evdev 模拟和读取按键
+#[cfg(target_os = "linux")]
+pub fn is_wayland() -> bool {
+    platform::is_wayland()
+}
+
+/// evdev 按键码在 US 布局下对应的字符，Wayland 下读取键盘设备时使用
+#[cfg(target_os = "linux")]
+pub fn evdev_char(code: u16, shift: bool) -> Option<char> {
+    platform::uinput::us_char(code, shift)
+}
+
+/// 逐个字符模拟按键输入文本，用于禁止粘贴的输入框和远程控制台
+pub fn type_text(text: &str, delay: Duration) -> Result<(), String> {
+    let text = text.replace("\r\n", "\n");
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    return platform::type_text(&text, delay);
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (text, delay);
+        Err("当前系统不支持模拟输入".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetKeyboardLayout, MapVirtualKeyExW, SendInput, VkKeyScanExW, HKL, INPUT, INPUT_0,
+        INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP,
+        KEYEVENTF_UNICODE, MAPVK_VK_TO_VSC_EX, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_INSERT,
+        VK_LEFT, VK_MENU, VK_SHIFT, VK_V,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowThreadProcessId, SetForegroundWindow,
+    };
+
+    use super::Key;
+
+    static ACTIVE_WINDOW: Mutex<Option<isize>> = Mutex::new(None);
+
+    pub fn remember_focus() {
+        let hwnd = unsafe { GetForegroundWindow() };
+        *ACTIVE_WINDOW.lock().unwrap() = (!hwnd.0.is_null()).then_some(hwnd.0 as isize);
+    }
+
+    pub fn restore_focus() {
+        if let Some(hwnd) = *ACTIVE_WINDOW.lock().unwrap() {
+            unsafe {
+                let _ = SetForegroundWindow(HWND(hwnd as *mut _));
+            }
+        }
+    }
+
+    fn virtual_key(key: Key) -> VIRTUAL_KEY {
+        match key {
+            Key::Control => VK_CONTROL,
+            Key::Shift => VK_SHIFT,
+            Key::V => VK_V,
+            Key::Insert => VK_INSERT,
+            Key::Left => VK_LEFT,
+            Key::Backspace => VK_BACK,
+        }
+    }
+
+    // 目标窗口的键盘布局，不同窗口可以用不同的输入法
+    fn foreground_layout() -> HKL {
+        unsafe { GetKeyboardLayout(GetWindowThreadProcessId(GetForegroundWindow(), None)) }
+    }
+
+    fn input(vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: scan,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    // 同时带上扫描码，远程控制台和虚拟机窗口按扫描码识别按键
+    fn chord(keys: &[VIRTUAL_KEY], layout: HKL) -> Vec<INPUT> {
+        // 方向键、Insert 等扩展键的扫描码带 0xE0 前缀，要加上 KEYEVENTF_EXTENDEDKEY
+        let key_input = |vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| {
+            let scan = unsafe { MapVirtualKeyExW(u32::from(vk.0), MAPVK_VK_TO_VSC_EX, layout) };
+            let flags = if scan & 0xff00 == 0xe000 {
+                flags | KEYEVENTF_EXTENDEDKEY
+            } else {
+                flags
+            };
+            input(vk, (scan & 0xff) as u16, flags)
+        };
+        keys.iter()
+            .map(|vk| key_input(*vk, Default::default()))
+            .chain(keys.iter().rev().map(|vk| key_input(*vk, KEYEVENTF_KEYUP)))
+            .collect()
+    }
+
+    fn send(inputs: &[INPUT]) -> Result<(), String> {
+        let result = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+        if result == 0 {
+            return Err("模拟按键失败".to_string());
+        }
+        Ok(())
+    }
+
+    // 按 count 次同一组按键，一次 SendInput 发完
+    pub fn send_keys(keys: &[Key], count: usize) -> Result<(), String> {
+        let keys: Vec<VIRTUAL_KEY> = keys.iter().map(|key| virtual_key(*key)).collect();
+        send(&chord(&keys, foreground_layout()).repeat(count))
+    }
+
+    pub fn type_text(text: &str, delay: Duration) -> Result<(), String> {
+        let layout = foreground_layout();
+        for ch in text.chars() {
+            send(&char_inputs(ch, layout))?;
+            std::thread::sleep(delay);
+        }
+        Ok(())
+    }
+
+    fn char_inputs(ch: char, layout: HKL) -> Vec<INPUT> {
+        // VkKeyScan 把 \n 当成 Ctrl+Enter
+        let ch = if ch == '\n' { '\r' } else { ch };
+        let mut buffer = [0u16; 2];
+        let units = ch.encode_utf16(&mut buffer);
+        // 当前布局能直接按出来的字符发送真实按键，远程控制台收不到 Unicode 事件
+        if let [unit] = units {
+            let scan = unsafe { VkKeyScanExW(*unit, layout) };
+            if scan != -1 {
+                let state = (scan >> 8) & 0xff;
+                let mut keys = Vec::new();
+                if state & 1 != 0 {
+                    keys.push(VK_SHIFT);
+                }
+                if state & 2 != 0 {
+                    keys.push(VK_CONTROL);
+                }
+                if state & 4 != 0 {
+                    keys.push(VK_MENU);
+                }
+                keys.push(VIRTUAL_KEY((scan & 0xff) as u16));
+                return chord(&keys, layout);
+            }
+        }
+        // 布局里没有的字符（中文、表情等）用 Unicode 事件输入
+        units
+            .iter()
+            .flat_map(|unit| {
+                [
+                    input(VIRTUAL_KEY(0), *unit, KEYEVENTF_UNICODE),
+                    input(VIRTUAL_KEY(0), *unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, InputFocus, Window,
+        KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+    };
+    use x11rb::protocol::xtest::ConnectionExt as _;
+    use x11rb::CURRENT_TIME;
+
+    use super::Key;
+    use crate::clipboard::log_line;
+
+    static ACTIVE_WINDOW: Mutex<Option<Window>> = Mutex::new(None);
+
+    pub fn is_wayland() -> bool {
+        std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+            || (std::env::var_os("WAYLAND_DISPLAY").is_some()
+                && std::env::var_os("DISPLAY").is_none())
+    }
+
+    pub fn remember_focus() {
+        if is_wayland() {
+            return;
+        }
+        let window = x11::active_window().unwrap_or_else(|err| {
+            log_line(&format!("paste: failed to read active window: {err}"));
+            None
+        });
+        *ACTIVE_WINDOW.lock().unwrap() = window;
+    }
+
+    pub fn restore_focus() {
+        let Some(window) = *ACTIVE_WINDOW.lock().unwrap() else {
+            return;
+        };
+        // 窗口管理器异步处理激活请求，等目标窗口真正拿到焦点再发送按键
+        let result = x11::activate(window)
+            .and_then(|_| x11::wait_for_active(window, Duration::from_millis(300)));
+        if let Err(err) = result {
+            log_line(&format!("paste: failed to restore focus: {err}"));
+        }
+    }
+
+    pub fn send_keys(keys: &[Key], count: usize) -> Result<(), String> {
+        if !is_wayland() {
+            return x11::send_keys(keys, count);
+        }
+        uinput::send_keys(keys, count).or_else(|err| {
+            log_line(&format!("paste: uinput failed ({err}), trying wtype"));
+            wtype(keys, count)
+        })
+    }
+
+    pub fn type_text(text: &str, delay: Duration) -> Result<(), String> {
+        if !is_wayland() {
+            return x11::type_text(text, delay);
+        }
+        // wtype 会上传自己的键盘映射，不受当前布局影响，优先使用
+        wtype_text(text, delay).or_else(|err| {
+            log_line(&format!("paste: wtype failed ({err}), trying uinput"));
+            uinput::type_text(text, delay)
+        })
+    }
+
+    // wtype 走 virtual-keyboard 协议，没有 /dev/uinput 权限时作为后备
+    fn wtype(keys: &[Key], count: usize) -> Result<(), String> {
+        let mut args = Vec::new();
+        for _ in 0..count {
+            for key in keys {
+                match key {
+                    Key::Control => args.extend(["-M", "ctrl"]),
+                    Key::Shift => args.extend(["-M", "shift"]),
+                    Key::V => args.extend(["-k", "v"]),
+                    Key::Insert => args.extend(["-k", "Insert"]),
+                    Key::Left => args.extend(["-k", "Left"]),
+                    Key::Backspace => args.extend(["-k", "BackSpace"]),
+                }
+            }
+            for key in keys.iter().rev() {
+                match key {
+                    Key::Control => args.extend(["-m", "ctrl"]),
+                    Key::Shift => args.extend(["-m", "shift"]),
+                    _ => {}
+                }
+            }
+        }
+        let status = Command::new("wtype")
+            .args(&args)
+            .status()
+            .map_err(|err| format!("无法模拟按键，请为 /dev/uinput 授权或安装 wtype: {err}"))?;
+        if !status.success() {
+            return Err(format!("wtype 执行失败: {status}"));
+        }
+        Ok(())
+    }
+
+    fn wtype_text(text: &str, delay: Duration) -> Result<(), String> {
+        // 文本从标准输入传入，避免以 - 开头的内容被当成参数
+        let mut child = Command::new("wtype")
+            .args(["-d", &delay.as_millis().to_string(), "-"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("无法启动 wtype: {err}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|err| err.to_string())?;
+        }
+        let status = child.wait().map_err(|err| err.to_string())?;
+        if !status.success() {
+            return Err(format!("wtype 执行失败: {status}"));
+        }
+        Ok(())
+    }
+
+    mod x11 {
+        use super::*;
+
+        fn keysym(key: Key) -> u32 {
+            match key {
+                Key::Control => 0xffe3,
+                Key::Shift => 0xffe1,
+                Key::V => 0x0076,
+                Key::Insert => 0xff63,
+                Key::Left => 0xff51,
+                Key::Backspace => 0xff08,
+            }
+        }
+
+        fn connect() -> Result<(x11rb::rust_connection::RustConnection, Window), String> {
+            let (conn, screen) =
+                x11rb::connect(None).map_err(|err| format!("无法连接 X 服务器: {err}"))?;
+            let root = conn.setup().roots[screen].root;
+            Ok((conn, root))
+        }
+
+        // 当前键盘布局的 keycode 到 keysym 映射
+        struct Keymap {
+            min_keycode: u8,
+            per_keycode: usize,
+            keysyms: Vec<u32>,
+            // 临时借来输入布局里没有的字符的空闲 keycode
+            spare: Option<(u8, u32)>,
+        }
+
+        impl Keymap {
+            fn load(conn: &impl Connection) -> Result<Self, String> {
+                let setup = conn.setup();
+                let (min, max) = (setup.min_keycode, setup.max_keycode);
+                let mapping = conn
+                    .get_keyboard_mapping(min, max - min + 1)
+                    .map_err(|err| err.to_string())?
+                    .reply()
+                    .map_err(|err| err.to_string())?;
+                Ok(Self {
+                    min_keycode: min,
+                    per_keycode: usize::from(mapping.keysyms_per_keycode).max(1),
+                    keysyms: mapping.keysyms,
+                    spare: None,
+                })
+            }
+
+            // 返回 keycode 和是否需要按住 Shift，优先不带 Shift 的按键
+            fn find(&self, keysym: u32) -> Option<(u8, bool)> {
+                [0, 1].into_iter().find_map(|level| {
+                    self.keysyms
+                        .chunks(self.per_keycode)
+                        .position(|keysyms| keysyms.get(level) == Some(&keysym))
+                        .map(|index| (self.min_keycode + index as u8, level == 1))
+                })
+            }
+
+            fn keycode(&self, keysym: u32) -> Result<u8, String> {
+                self.find(keysym)
+                    .map(|(code, _)| code)
+                    .ok_or_else(|| format!("键盘布局中没有 keysym {keysym:#x}"))
+            }
+
+            // 把没有任何 keysym 的 keycode 临时映射成要输入的字符，和 xdotool 的做法一样
+            fn borrow_spare(&mut self, conn: &impl Connection, keysym: u32) -> Result<u8, String> {
+                let code = match self.spare {
+                    Some((code, mapped)) if mapped == keysym => return Ok(code),
+                    Some((code, _)) => code,
+                    None => self
+                        .keysyms
+                        .chunks(self.per_keycode)
+                        .rposition(|keysyms| keysyms.iter().all(|keysym| *keysym == 0))
+                        .map(|index| self.min_keycode + index as u8)
+                        .ok_or_else(|| "没有空闲的 keycode 用来输入特殊字符".to_string())?,
+                };
+                self.remap(conn, code, keysym)?;
+                self.spare = Some((code, keysym));
+                // 等其他程序收到 MappingNotify 更新映射后再按键
+                std::thread::sleep(Duration::from_millis(30));
+                Ok(code)
+            }
+
+            fn release_spare(&mut self, conn: &impl Connection) -> Result<(), String> {
+                match self.spare.take() {
+                    Some((code, _)) => self.remap(conn, code, 0),
+                    None => Ok(()),
+                }
+            }
+
+            fn remap(&self, conn: &impl Connection, code: u8, keysym: u32) -> Result<(), String> {
+                let keysyms = vec![keysym; self.per_keycode];
+                conn.change_keyboard_mapping(1, code, self.per_keycode as u8, &keysyms)
+                    .map_err(|err| err.to_string())?;
+                sync(conn)
+            }
+        }
+
+        // 等服务器处理完之前的请求
+        fn sync(conn: &impl Connection) -> Result<(), String> {
+            conn.get_input_focus()
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?;
+            Ok(())
+        }
+
+        fn fake_keys(conn: &impl Connection, root: Window, codes: &[u8]) -> Result<(), String> {
+            let events = codes
+                .iter()
+                .map(|code| (KEY_PRESS_EVENT, *code))
+                .chain(codes.iter().rev().map(|code| (KEY_RELEASE_EVENT, *code)));
+            for (event, code) in events {
+                conn.xtest_fake_input(event, code, CURRENT_TIME, root, 0, 0, 0)
+                    .map_err(|err| err.to_string())?;
+            }
+            Ok(())
+        }
+
+        // 同一个连接和键盘映射发完 count 次按键
+        pub fn send_keys(keys: &[Key], count: usize) -> Result<(), String> {
+            let (conn, root) = connect()?;
+            let keymap = Keymap::load(&conn)?;
+            let codes = keys
+                .iter()
+                .map(|key| keymap.keycode(keysym(*key)))
+                .collect::<Result<Vec<_>, _>>()?;
+            for _ in 0..count {
+                fake_keys(&conn, root, &codes)?;
+            }
+            // 等服务器处理完再断开连接
+            sync(&conn)
+        }
+
+        fn char_keysym(ch: char) -> u32 {
+            match ch {
+                '\n' => 0xff0d,
+                '\t' => 0xff09,
+                ' '..='~' | '\u{a0}'..='\u{ff}' => ch as u32,
+                _ => 0x0100_0000 | ch as u32,
+            }
+        }
+
+        pub fn type_text(text: &str, delay: Duration) -> Result<(), String> {
+            let (conn, root) = connect()?;
+            let mut keymap = Keymap::load(&conn)?;
+            let shift = keymap.keycode(keysym(Key::Shift))?;
+            let mut result = Ok(());
+            for ch in text.chars() {
+                let keysym = char_keysym(ch);
+                result = match keymap.find(keysym) {
+                    Some((code, true)) => Ok(vec![shift, code]),
+                    Some((code, false)) => Ok(vec![code]),
+                    None => keymap.borrow_spare(&conn, keysym).map(|code| vec![code]),
+                }
+                .and_then(|codes| fake_keys(&conn, root, &codes))
+                .and_then(|_| sync(&conn));
+                if result.is_err() {
+                    break;
+                }
+                std::thread::sleep(delay);
+            }
+            // 输入完还原借用的 keycode
+            let released = keymap.release_spare(&conn);
+            result.and(released)
+        }
+
+        fn active_window_atom(conn: &impl Connection) -> Result<u32, String> {
+            Ok(conn
+                .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?
+                .atom)
+        }
+
+        pub fn active_window() -> Result<Option<Window>, String> {
+            let (conn, root) = connect()?;
+            let atom = active_window_atom(&conn)?;
+            read_active_window(&conn, root, atom)
+        }
+
+        // 轮询直到 window 成为活动窗口，超时后照常继续
+        pub fn wait_for_active(window: Window, timeout: Duration) -> Result<(), String> {
+            let (conn, root) = connect()?;
+            let atom = active_window_atom(&conn)?;
+            let deadline = std::time::Instant::now() + timeout;
+            while read_active_window(&conn, root, atom)? != Some(window) {
+                if std::time::Instant::now() >= deadline {
+                    log_line("paste: target window did not become active in time");
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Ok(())
+        }
+
+        fn read_active_window(
+            conn: &impl Connection,
+            root: Window,
+            atom: u32,
+        ) -> Result<Option<Window>, String> {
+            let reply = conn
+                .get_property(false, root, atom, AtomEnum::WINDOW, 0, 1)
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?;
+            if let Some(window) = reply.value32().and_then(|mut values| values.next()) {
+                if window != 0 {
+                    return Ok(Some(window));
+                }
+            }
+            // 没有支持 EWMH 的窗口管理器时退回到输入焦点
+            let focus = conn
+                .get_input_focus()
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?
+                .focus;
+            Ok((focus > 1).then_some(focus))
+        }
+
+        pub fn activate(window: Window) -> Result<(), String> {
+            let (conn, root) = connect()?;
+            let atom = active_window_atom(&conn)?;
+            // source = 2 表示请求来自用户操作，窗口管理器不会拦截
+            let event = ClientMessageEvent::new(32, window, atom, [2, CURRENT_TIME, 0, 0, 0]);
+            conn.send_event(
+                false,
+                root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(|err| err.to_string())?;
+            conn.set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+                .map_err(|err| err.to_string())?;
+            conn.get_input_focus()
+                .map_err(|err| err.to_string())?
+                .reply()
+                .map_err(|err| err.to_string())?;
+            Ok(())
+        }
+    }
+
+    pub mod uinput {
+        use std::fs::{File, OpenOptions};
+        use std::io::Write;
+        use std::os::fd::AsRawFd;
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        use super::Key;
+
+        const EV_SYN: u16 = 0x00;
+        const EV_KEY: u16 = 0x01;
+        const SYN_REPORT: u16 = 0;
+        const BUS_VIRTUAL: u16 = 0x06;
+        // KEY_INSERT 之后是多媒体键，用不到
+        const KEY_LAST: u16 = 110;
+
+        const UI_DEV_CREATE: libc::Ioctl = libc::_IO(b'U' as u32, 1);
+        const UI_DEV_SETUP: libc::Ioctl = libc::_IOW::<libc::uinput_setup>(b'U' as u32, 3);
+        const UI_SET_EVBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(b'U' as u32, 100);
+        const UI_SET_KEYBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(b'U' as u32, 101);
+
+        // 虚拟键盘只在第一次粘贴时创建，之后一直复用
+        static DEVICE: Mutex<Option<File>> = Mutex::new(None);
+
+        // Linux 输入子系统的按键码对应物理位置，按 US 布局发送
+        fn code(key: Key) -> u16 {
+            match key {
+                Key::Control => 29,
+                Key::Shift => 42,
+                Key::V => 47,
+                Key::Insert => 110,
+                Key::Left => 105,
+                Key::Backspace => 14,
+            }
+        }
+
+        // US 布局主键盘区每一行：不按 Shift 的字符、按住 Shift 的字符、第一个键的按键码
+        const ROWS: [(&str, &str, u16); 4] = [
+            ("1234567890-=", "!@#$%^&*()_+", 2),
+            ("qwertyuiop[]", "QWERTYUIOP{}", 16),
+            ("asdfghjkl;'`", "ASDFGHJKL:\"~", 30),
+            ("zxcvbnm,./", "ZXCVBNM<>?", 44),
+        ];
+        const OTHER_KEYS: [(char, u16, bool); 5] = [
+            ('\t', 15, false),
+            ('\n', 28, false),
+            ('\\', 43, false),
+            ('|', 43, true),
+            (' ', 57, false),
+        ];
+
+        // 字符在 US 布局里的按键码和是否需要 Shift
+        fn us_key(ch: char) -> Option<(u16, bool)> {
+            if let Some((_, code, shift)) = OTHER_KEYS.iter().find(|(other, ..)| *other == ch) {
+                return Some((*code, *shift));
+            }
+            ROWS.iter().find_map(|(plain, shifted, first)| {
+                let (index, shift) = match plain.find(ch) {
+                    Some(index) => (index, false),
+                    None => (shifted.find(ch)?, true),
+                };
+                Some((first + index as u16, shift))
+            })
+        }
+
+        pub fn us_char(code: u16, shift: bool) -> Option<char> {
+            if let Some((ch, ..)) = OTHER_KEYS
+                .iter()
+                .find(|(_, other, other_shift)| *other == code && *other_shift == shift)
+            {
+                return Some(*ch);
+            }
+            ROWS.iter().find_map(|(plain, shifted, first)| {
+                let index = usize::from(code.checked_sub(*first)?);
+                let row = if shift { shifted } else { plain };
+                row.chars().nth(index)
+            })
+        }
+
+        fn with_device<T>(f: impl FnOnce(&mut File) -> Result<T, String>) -> Result<T, String> {
+            let mut device = DEVICE.lock().unwrap();
+            if device.is_none() {
+                *device = Some(create()?);
+            }
+            f(device.as_mut().unwrap())
+        }
+
+        fn press(file: &mut File, codes: &[u16]) -> Result<(), String> {
+            for code in codes {
+                emit(file, EV_KEY, *code, 1)?;
+                emit(file, EV_SYN, SYN_REPORT, 0)?;
+            }
+            for code in codes.iter().rev() {
+                emit(file, EV_KEY, *code, 0)?;
+                emit(file, EV_SYN, SYN_REPORT, 0)?;
+            }
+            Ok(())
+        }
+
+        pub fn send_keys(keys: &[Key], count: usize) -> Result<(), String> {
+            let codes: Vec<u16> = keys.iter().map(|key| code(*key)).collect();
+            with_device(|file| (0..count).try_for_each(|_| press(file, &codes)))
+        }
+
+        // 虚拟键盘发送的是物理按键，由合成器按当前布局解释，只能可靠输入 US 布局下的 ASCII 字符
+        pub fn type_text(text: &str, delay: Duration) -> Result<(), String> {
+            let keys = text
+                .chars()
+                .map(|ch| {
+                    us_key(ch)
+                        .ok_or_else(|| format!("无法通过虚拟键盘输入字符 {ch:?}，请安装 wtype"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            with_device(|file| {
+                for (key, shift) in keys {
+                    if shift {
+                        press(file, &[code(Key::Shift), key])?;
+                    } else {
+                        press(file, &[key])?;
+                    }
+                    std::thread::sleep(delay);
+                }
+                Ok(())
+            })
+        }
+
+        fn create() -> Result<File, String> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|err| format!("无法打开 /dev/uinput: {err}"))?;
+            let fd = file.as_raw_fd();
+            let check = |result: libc::c_int, action: &str| {
+                if result < 0 {
+                    Err(format!("{action}失败: {}", std::io::Error::last_os_error()))
+                } else {
+                    Ok(())
+                }
+            };
+            unsafe {
+                check(
+                    libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_KEY)),
+                    "设置事件类型",
+                )?;
+                // 注册主键盘区的全部按键，逐字输入时也要用到
+                for code in 1..=KEY_LAST {
+                    check(
+                        libc::ioctl(fd, UI_SET_KEYBIT, libc::c_int::from(code)),
+                        "设置按键",
+                    )?;
+                }
+                let mut setup: libc::uinput_setup = std::mem::zeroed();
+                setup.id.bustype = BUS_VIRTUAL;
+                for (target, byte) in setup.name.iter_mut().zip(b"paste_app keyboard") {
+                    *target = *byte as libc::c_char;
+                }
+                check(libc::ioctl(fd, UI_DEV_SETUP, &setup), "创建虚拟键盘")?;
+                check(libc::ioctl(fd, UI_DEV_CREATE), "创建虚拟键盘")?;
+            }
+            // 新设备要过一会儿才会被合成器识别，太早发送的按键会丢失
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(file)
+        }
+
+        fn emit(file: &mut File, kind: u16, code: u16, value: i32) -> Result<(), String> {
+            let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+            event.type_ = kind;
+            event.code = code;
+            event.value = value;
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    (&event as *const libc::input_event).cast::<u8>(),
+                    std::mem::size_of::<libc::input_event>(),
+                )
+            };
+            file.write_all(bytes)
+                .map_err(|err| format!("写入 /dev/uinput 失败: {err}"))
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::paste::PasteMode;
+use crate::paste_input::PasteChord;
+use crate::settings::Settings;
+use crate::transforms::Transform;
+
+/// 按目标程序调整粘贴方式，呼出弹窗时的前台程序命中 apps 中任意一个时生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteProfile {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 程序的进程名，例如 WindowsTerminal.exe 或 kitty，不区分大小写
+    pub apps: Vec<String>,
+    /// 为空时使用设置中的 pasteChord
+    #[serde(default)]
+    pub paste_chord: Option<PasteChord>,
+    /// 为空时使用设置中的 defaultPasteMode
+    #[serde(default)]
+    pub paste_mode: Option<PasteMode>,
+    /// 粘贴前按顺序对文本执行的转换
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 返回第一个匹配目标程序的配置
+pub fn find<'a>(settings: &'a Settings, app: Option<&str>) -> Option<&'a PasteProfile> {
+    let app = app?;
+    settings.paste_profiles.iter().find(|profile| {
+        profile.enabled
+            && profile
+                .apps
+                .iter()
+                .any(|expected| expected.eq_ignore_ascii_case(app))
+    })
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::clipboard::log_line;
+use crate::models::ClipboardItemRow;
+
+/// 队列变化时发给前端的事件，payload 为 QueueStatus
+pub const STATUS_EVENT: &str = "paste-queue://status";
+
+/// 连续粘贴时的出队顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueueOrder {
+    /// 先进先出，按选择或复制的顺序粘贴
+    #[default]
+    Fifo,
+    /// 后进先出，最后加入的先粘贴
+    Lifo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatus {
+    pub active: bool,
+    /// 正在收集新复制的内容
+    pub collecting: bool,
+    pub order: QueueOrder,
+    /// 按接下来粘贴的顺序排列
+    pub item_ids: Vec<i64>,
+}
+
+#[derive(Default)]
+struct PasteQueue {
+    // 直接保存整条记录，出队和预先写入剪贴板时不用再查数据库
+    items: VecDeque<ClipboardItemRow>,
+    order: QueueOrder,
+    collecting: bool,
+    // 已经写入剪贴板、等待被粘贴的记录
+    prefetched: Option<i64>,
+}
+
+impl PasteQueue {
+    fn head(&self) -> Option<&ClipboardItemRow> {
+        match self.order {
+            QueueOrder::Fifo => self.items.front(),
+            QueueOrder::Lifo => self.items.back(),
+        }
+    }
+}
+
+static QUEUE: OnceLock<Mutex<PasteQueue>> = OnceLock::new();
+
+fn queue() -> &'static Mutex<PasteQueue> {
+    QUEUE.get_or_init(|| Mutex::new(PasteQueue::default()))
+}
+
+/// 用选中的记录开始一个新队列，替换掉之前的队列
+pub fn start(handle: &AppHandle, rows: Vec<ClipboardItemRow>, order: QueueOrder) {
+    {
+        let mut queue = queue().lock().unwrap();
+        queue.items = rows.into();
+        queue.order = order;
+        queue.collecting = false;
+        queue.prefetched = None;
+    }
+    emit_status(handle);
+}
+
+/// 清空队列，之后复制的内容依次加入队列，直到开始粘贴
+pub fn start_collecting(handle: &AppHandle, order: QueueOrder) {
+    {
+        let mut queue = queue().lock().unwrap();
+        queue.items.clear();
+        queue.order = order;
+        queue.collecting = true;
+        queue.prefetched = None;
+    }
+    emit_status(handle);
+}
+
+pub fn is_collecting() -> bool {
+    queue().lock().unwrap().collecting
+}
+
+/// 收集模式下把新捕获的记录加入队列
+pub fn collect(handle: &AppHandle, row: ClipboardItemRow) {
+    {
+        let mut queue = queue().lock().unwrap();
+        if !queue.collecting {
+            return;
+        }
+        log_line(&format!("paste queue: collected item {}", row.id));
+        queue.items.push_back(row);
+    }
+    emit_status(handle);
+}
+
+/// 取出下一条要粘贴的记录；第一次出队时结束收集
+pub fn pop(handle: &AppHandle) -> Option<ClipboardItemRow> {
+    let row = {
+        let mut queue = queue().lock().unwrap();
+        let row = match queue.order {
+            QueueOrder::Fifo => queue.items.pop_front(),
+            QueueOrder::Lifo => queue.items.pop_back(),
+        }?;
+        queue.collecting = false;
+        queue.prefetched = None;
+        row
+    };
+    log_line(&format!("paste queue: popped item {}", row.id));
+    emit_status(handle);
+    Some(row)
+}
+
+/// 下一条要粘贴的记录，不出队
+pub fn peek() -> Option<ClipboardItemRow> {
+    queue().lock().unwrap().head().cloned()
+}
+
+/// 记下已经写入剪贴板的队首记录
+pub fn mark_prefetched(id: i64) {
+    queue().lock().unwrap().prefetched = Some(id);
+}
+
+/// 队列为空时返回 None，否则返回队首是否已经写入剪贴板；键盘钩子里调用，只读内存状态
+pub fn head_prefetched() -> Option<bool> {
+    let queue = queue().lock().unwrap();
+    let head = queue.head()?;
+    Some(queue.prefetched == Some(head.id))
+}
+
+pub fn clear(handle: &AppHandle) {
+    {
+        let mut queue = queue().lock().unwrap();
+        queue.items.clear();
+        queue.collecting = false;
+        queue.prefetched = None;
+    }
+    emit_status(handle);
+}
+
+pub fn status() -> QueueStatus {
+    let queue = queue().lock().unwrap();
+    let ids = queue.items.iter().map(|row| row.id);
+    let item_ids = match queue.order {
+        QueueOrder::Fifo => ids.collect(),
+        QueueOrder::Lifo => ids.rev().collect(),
+    };
+    QueueStatus {
+        active: queue.collecting || !queue.items.is_empty(),
+        collecting: queue.collecting,
+        order: queue.order,
+        item_ids,
+    }
+}
+
+fn emit_status(handle: &AppHandle) {
+    if let Err(err) = handle.emit(STATUS_EVENT, status()) {
+        log_line(&format!("paste queue: status emit failed: {err}"));
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::{NewPathEntry, PathKind};
+
+// 超过这个行数的文本不再当作路径列表，避免对大段日志逐行 stat
+const MAX_PATHS: usize = 200;
+
+/// 解析单个路径或每行一个路径的列表，任何一行不像路径时返回 None
+pub fn parse_path_list(text: &str) -> Option<Vec<String>> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() || lines.len() > MAX_PATHS {
+        return None;
+    }
+    lines.into_iter().map(parse_path_line).collect()
+}
+
+fn parse_path_line(line: &str) -> Option<String> {
+    let (path, quoted) = match strip_quotes(line) {
+        Some(inner) => (inner, true),
+        None => (line, false),
+    };
+    if path.is_empty() || path.contains(['<', '>', '|', '"', '\t']) {
+        return None;
+    }
+    // 代码注释不是路径
+    if path.starts_with("/*") || (path.starts_with("//") && !path[2..].contains('/')) {
+        return None;
+    }
+
+    if is_windows_path(path) {
+        // Windows 路径里空格很常见（Program Files），但带参数的命令行不算
+        if !quoted && (path.contains(" -") || path.contains(" /")) {
+            return None;
+        }
+        return Some(path.to_string());
+    }
+    if !is_unix_path(path) {
+        return None;
+    }
+    if quoted || !path.contains(char::is_whitespace) {
+        return Some(path.to_string());
+    }
+    // `/usr/bin/foo bar baz` 更像一条命令；只有转义了空格或者磁盘上确实存在时才算路径
+    if path.contains("\\ ") {
+        return Some(path.replace("\\ ", " "));
+    }
+    Path::new(&expand_home(path))
+        .exists()
+        .then(|| path.to_string())
+}
+
+fn strip_quotes(line: &str) -> Option<&str> {
+    ['"', '\''].into_iter().find_map(|quote| {
+        line.strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+    })
+}
+
+fn is_windows_path(path: &str) -> bool {
+    if path.starts_with("\\\\") {
+        return true;
+    }
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/')
+}
+
+fn is_unix_path(path: &str) -> bool {
+    path.starts_with('/')
+        || path == "~"
+        || path.starts_with("~/")
+        || path.starts_with("$HOME/")
+        || path.starts_with("${HOME}/")
+}
+
+fn is_network_path(path: &str) -> bool {
+    path.starts_with("\\\\") || path.starts_with("//")
+}
+
+/// 展开开头的 ~ 和 $HOME
+pub fn expand_home(path: &str) -> String {
+    let Some(home) = home_dir() else {
+        return path.to_string();
+    };
+    for prefix in ["${HOME}", "$HOME", "~"] {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') {
+                return format!("{home}{rest}");
+            }
+        }
+    }
+    path.to_string()
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .filter(|home| !home.is_empty())
+}
+
+/// 检查路径当前的状态；check_network 为 false 时跳过网络路径，避免在剪贴板线程上卡住
+pub fn inspect(path: &str, check_network: bool, now: i64) -> NewPathEntry {
+    let expanded = expand_home(path);
+    let mut entry = NewPathEntry {
+        path: path.to_string(),
+        expanded,
+        exists: None,
+        kind: None,
+        size: None,
+        checked_at: None,
+    };
+    if !check_network && is_network_path(&entry.expanded) {
+        return entry;
+    }
+    match fs::metadata(&entry.expanded) {
+        Ok(meta) => {
+            entry.exists = Some(true);
+            if meta.is_dir() {
+                entry.kind = Some(PathKind::Directory);
+            } else {
+                entry.kind = Some(PathKind::File);
+                entry.size = Some(meta.len() as i64);
+            }
+        }
+        Err(_) => entry.exists = Some(false),
+    }
+    entry.checked_at = Some(now);
+    entry
+}
+
+/// 在系统文件管理器中显示路径；文件会被选中，不存在时打开最近的上级目录
+pub fn reveal_in_folder(path: &str) -> Result<(), String> {
+    let expanded = expand_home(path);
+    let target = Path::new(&expanded);
+    let existing = target
+        .ancestors()
+        .find(|candidate| candidate.exists())
+        .ok_or_else(|| format!("路径不存在: {path}"))?;
+    let select = existing == target && target.is_file();
+
+    #[cfg(target_os = "windows")]
+    let result = if select {
+        Command::new("explorer")
+            .arg(format!("/select,{}", existing.display()))
+            .spawn()
+    } else {
+        Command::new("explorer").arg(existing).spawn()
+    };
+    #[cfg(target_os = "macos")]
+    let result = if select {
+        Command::new("open").arg("-R").arg(existing).spawn()
+    } else {
+        Command::new("open").arg(existing).spawn()
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = {
+        // xdg-open 没有选中文件的参数，只能打开所在目录
+        let folder = if select {
+            existing.parent().unwrap_or(existing)
+        } else {
+            existing
+        };
+        Command::new("xdg-open").arg(folder).spawn()
+    };
+
+    result
+        .map(|_| ())
+        .map_err(|err| format!("打开文件夹失败: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_paths() {
+        let cases = [
+            ("/usr/bin/env", Some("/usr/bin/env")),
+            ("~/Documents/notes.txt", Some("~/Documents/notes.txt")),
+            ("$HOME/.config", Some("$HOME/.config")),
+            ("${HOME}/.config", Some("${HOME}/.config")),
+            ("\"/tmp/my file.txt\"", Some("/tmp/my file.txt")),
+            ("'/tmp/my file.txt'", Some("/tmp/my file.txt")),
+            ("/tmp/my\\ file.txt", Some("/tmp/my file.txt")),
+            (
+                r"C:\Program Files\App\app.exe",
+                Some(r"C:\Program Files\App\app.exe"),
+            ),
+            ("C:/Users/ann/report.docx", Some("C:/Users/ann/report.docx")),
+            (r"\\server\share\file.txt", Some(r"\\server\share\file.txt")),
+            ("//server/share/file.txt", Some("//server/share/file.txt")),
+            (
+                r#""C:\Program Files\App\app.exe -v""#,
+                Some(r"C:\Program Files\App\app.exe -v"),
+            ),
+            (r"C:\Program Files\App\app.exe -v", None),
+            (r"C:\Windows\System32\cmd.exe /c dir", None),
+            ("// TODO: remove this", None),
+            ("/* block comment */", None),
+            ("/usr/bin/foo bar baz", None),
+            ("relative/path.txt", None),
+            ("~user/file", None),
+            ("$HOMEDIR/file", None),
+            ("/tmp/a|b", None),
+            ("\"\"", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_path_line(line).as_deref(), expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn parses_path_lists() {
+        assert_eq!(
+            parse_path_list("/etc/hosts\n\n  ~/notes.md  \n\"/tmp/a b\"\n"),
+            Some(vec![
+                "/etc/hosts".to_string(),
+                "~/notes.md".to_string(),
+                "/tmp/a b".to_string()
+            ])
+        );
+        assert_eq!(parse_path_list("/etc/hosts\nnot a path"), None);
+        assert_eq!(parse_path_list("  \n "), None);
+        let too_many = vec!["/tmp/x"; MAX_PATHS + 1].join("\n");
+        assert_eq!(parse_path_list(&too_many), None);
+    }
+
+    #[test]
+    fn unquoted_spaces_need_an_existing_path() {
+        let dir = std::env::temp_dir().join(format!("clip-paths-{}", std::process::id()));
+        let file = dir.join("my notes.txt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&file, "x").unwrap();
+        let existing = file.to_string_lossy().into_owned();
+        let missing = dir.join("other notes.txt").to_string_lossy().into_owned();
+
+        let parsed_existing = parse_path_line(&existing);
+        let parsed_missing = parse_path_line(&missing);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(parsed_existing, Some(existing));
+        assert_eq!(parsed_missing, None);
+    }
+
+    #[test]
+    fn expands_home_prefixes() {
+        let Some(home) = home_dir() else {
+            return;
+        };
+        let cases = [
+            ("~", home.clone()),
+            ("~/a", format!("{home}/a")),
+            ("$HOME/a", format!("{home}/a")),
+            ("${HOME}/a", format!("{home}/a")),
+            ("~\\a", format!("{home}\\a")),
+            ("~user/a", "~user/a".to_string()),
+            ("$HOMEDIR/a", "$HOMEDIR/a".to_string()),
+            ("/a/~/b", "/a/~/b".to_string()),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(expand_home(path), expected, "{path}");
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use image::{imageops, ImageBuffer, Rgba};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::clipboard::log_line;
+use crate::db;
+
+/// 未配置阈值时，汉明距离不超过这个值就算近似重复
+pub const DEFAULT_THRESHOLD: u32 = 4;
+
+// 查找近似重复时只比较最近的这些图片
+const RECENT_IMAGES: i64 = 200;
+
+/// 捕获到与已有图片近似重复的截图时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageDuplicatePolicy {
+    /// 照常保存
+    #[default]
+    Keep,
+    /// 不保存新截图
+    Skip,
+    /// 把已有记录移到最前面，分辨率更高时用新截图替换
+    Merge,
+}
+
+/// dHash：缩到 9x8 的灰度图后比较相邻像素的明暗，得到 64 位指纹
+pub fn dhash(rgba: &[u8], width: u32, height: u32) -> Option<u64> {
+    let image = ImageBuffer::<Rgba<u8>, &[u8]>::from_raw(width, height, rgba)?;
+    let small = imageops::thumbnail(&image, 9, 8);
+    let luma = |x: u32, y: u32| {
+        let [r, g, b, _] = small.get_pixel(x, y).0;
+        u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114
+    };
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if luma(x, y) < luma(x + 1, y) {
+                hash |= 1;
+            }
+        }
+    }
+    Some(hash)
+}
+
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 在最近的图片中找与指纹最接近、且距离不超过阈值的一条
+pub async fn find_near_duplicate(
+    pool: &SqlitePool,
+    hash: u64,
+    threshold: u32,
+) -> Result<Option<i64>, sqlx::Error> {
+    let candidates = db::list_image_hashes(pool, Some(RECENT_IMAGES)).await?;
+    Ok(candidates
+        .into_iter()
+        .map(|(id, other)| (id, distance(hash, other as u64)))
+        .filter(|(_, distance)| *distance <= threshold)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(id, _)| id))
+}
+
+/// 给升级前保存的图片补算指纹
+pub fn start_backfill(pool: SqlitePool) {
+    tauri::async_runtime::spawn(async move {
+        let mut before = i64::MAX;
+        loop {
+            let pending = match db::next_image_without_hash(&pool, before).await {
+                Ok(Some(pending)) => pending,
+                Ok(None) => break,
+                Err(err) => {
+                    log_line(&format!("phash: failed to load image: {err}"));
+                    break;
+                }
+            };
+            let (id, bytes, width, height) = pending;
+            before = id;
+            let hash = tauri::async_runtime::spawn_blocking(move || {
+                dhash(&bytes, width.try_into().ok()?, height.try_into().ok()?)
+            })
+            .await
+            .ok()
+            .flatten();
+            // 无法解码的图片保持为空，按 id 递减遍历所以不会重复处理
+            let Some(hash) = hash else {
+                continue;
+            };
+            if let Err(err) = db::save_image_hash(&pool, id, hash as i64).await {
+                log_line(&format!("phash: failed to save hash for item {id}: {err}"));
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按归一化坐标生成图片，同一个图案可以用不同尺寸和偏移渲染
+    fn render(width: u32, height: u32, shift: u32, pattern: fn(f32, f32) -> u8) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x + shift) as f32 / width as f32;
+                let v = y as f32 / height as f32;
+                let value = pattern(u, v);
+                rgba.extend_from_slice(&[value, value / 2, 255 - value, 255]);
+            }
+        }
+        rgba
+    }
+
+    fn window(u: f32, v: f32) -> u8 {
+        // 类似截图：浅色背景上的标题栏、侧边栏和几块内容
+        if v < 0.1 {
+            60
+        } else if u < 0.25 {
+            120
+        } else if (0.35..0.9).contains(&u) && (0.2..0.45).contains(&v) {
+            30
+        } else if (0.35..0.6).contains(&u) && (0.55..0.9).contains(&v) {
+            200
+        } else {
+            240
+        }
+    }
+
+    fn gradient(u: f32, v: f32) -> u8 {
+        (((u * 3.0).sin() * (v * 5.0).cos() + 1.0) * 127.0) as u8
+    }
+
+    fn rings(u: f32, v: f32) -> u8 {
+        let r = ((u - 0.3).powi(2) + (v - 0.7).powi(2)).sqrt();
+        if (r * 6.0).fract() < 0.5 {
+            20
+        } else {
+            230
+        }
+    }
+
+    fn hash(width: u32, height: u32, shift: u32, pattern: fn(f32, f32) -> u8) -> u64 {
+        dhash(&render(width, height, shift, pattern), width, height).unwrap()
+    }
+
+    #[test]
+    fn shifted_and_rescaled_images_stay_close() {
+        for pattern in [window as fn(f32, f32) -> u8, gradient, rings] {
+            let original = hash(400, 300, 0, pattern);
+            let shifted = hash(400, 300, 1, pattern);
+            let doubled = hash(800, 600, 0, pattern);
+            assert!(distance(original, shifted) <= DEFAULT_THRESHOLD);
+            assert!(distance(original, doubled) <= DEFAULT_THRESHOLD);
+        }
+    }
+
+    #[test]
+    fn unrelated_images_are_far_apart() {
+        let hashes = [
+            hash(400, 300, 0, window),
+            hash(400, 300, 0, gradient),
+            hash(400, 300, 0, rings),
+        ];
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert!(
+                    distance(*a, *b) > DEFAULT_THRESHOLD * 2,
+                    "{a:064b} {b:064b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_buffers() {
+        assert_eq!(dhash(&[0; 12], 2, 2), None);
+    }
+}
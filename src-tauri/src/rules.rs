@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::clipboard::log_line;
+use crate::settings::{self, Settings};
+
+/// 用户自定义的分类规则，所有填写的条件都满足时命中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassificationRule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 来源应用的进程名，例如 Code.exe，不区分大小写。只有 Windows 和 X11 能取到来源应用，
+    /// Wayland 和 macOS 下填写了这一项的规则不会命中
+    #[serde(default)]
+    pub source_app: Option<String>,
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 规则匹配的结果：第一个带分类的规则决定自定义分类，标签累加
+#[derive(Debug, Default)]
+pub struct RuleMatch {
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+}
+
+struct CompiledRule {
+    rule: ClassificationRule,
+    regex: Option<Regex>,
+}
+
+type CompiledCache = (Arc<Settings>, Arc<Vec<CompiledRule>>);
+
+static COMPILED: OnceLock<Mutex<Option<CompiledCache>>> = OnceLock::new();
+
+/// 在内置分类之后按顺序执行用户规则
+pub fn apply(text: Option<&str>, source_app: Option<&str>) -> RuleMatch {
+    let rules = compiled_rules();
+    let mut result = RuleMatch::default();
+    for compiled in rules.iter() {
+        if !matches(compiled, text, source_app) {
+            continue;
+        }
+        if result.category.is_none() {
+            result.category.clone_from(&compiled.rule.category);
+        }
+        for tag in &compiled.rule.tags {
+            if !result.tags.contains(tag) {
+                result.tags.push(tag.clone());
+            }
+        }
+    }
+    result
+}
+
+// 设置文件热更新后重新编译正则，未变化时复用缓存
+fn compiled_rules() -> Arc<Vec<CompiledRule>> {
+    let current = settings::current();
+    let cache = COMPILED.get_or_init(|| Mutex::new(None));
+    let mut cache = cache.lock().unwrap();
+    if let Some((settings, rules)) = cache.as_ref() {
+        if Arc::ptr_eq(settings, &current) {
+            return rules.clone();
+        }
+    }
+
+    let rules: Vec<CompiledRule> = current
+        .classification_rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| {
+            // 手动编辑 settings.json 时可能绕过保存时的校验，没有条件的规则一律不命中
+            if !has_conditions(rule) {
+                log_line(&format!(
+                    "rules: skip rule {} without conditions",
+                    rule.name
+                ));
+                return None;
+            }
+            let regex = match rule.regex.as_deref().filter(|p| !p.is_empty()) {
+                Some(pattern) => match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(err) => {
+                        log_line(&format!("rules: skip rule {}: {err}", rule.name));
+                        return None;
+                    }
+                },
+                None => None,
+            };
+            Some(CompiledRule {
+                rule: rule.clone(),
+                regex,
+            })
+        })
+        .collect();
+    let rules = Arc::new(rules);
+    *cache = Some((current, rules.clone()));
+    rules
+}
+
+/// 保存设置前校验：没有任何条件的规则会命中所有记录
+pub fn validate(rules: &[ClassificationRule]) -> Result<(), String> {
+    match rules.iter().find(|rule| !has_conditions(rule)) {
+        Some(rule) => Err(format!("规则 {} 至少需要填写一个条件", rule.name)),
+        None => Ok(()),
+    }
+}
+
+fn has_conditions(rule: &ClassificationRule) -> bool {
+    let filled = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.is_empty());
+    filled(&rule.regex)
+        || filled(&rule.prefix)
+        || filled(&rule.source_app)
+        || rule.min_length.is_some()
+        || rule.max_length.is_some()
+}
+
+fn matches(compiled: &CompiledRule, text: Option<&str>, source_app: Option<&str>) -> bool {
+    let rule = &compiled.rule;
+    if let Some(expected) = rule.source_app.as_deref().filter(|app| !app.is_empty()) {
+        match source_app {
+            Some(app) if app.eq_ignore_ascii_case(expected) => {}
+            _ => return false,
+        }
+    }
+
+    let needs_text = compiled.regex.is_some()
+        || rule.prefix.as_deref().is_some_and(|p| !p.is_empty())
+        || rule.min_length.is_some()
+        || rule.max_length.is_some();
+    let Some(text) = text else {
+        return !needs_text;
+    };
+
+    if let Some(prefix) = rule.prefix.as_deref().filter(|p| !p.is_empty()) {
+        if !text.starts_with(prefix) {
+            return false;
+        }
+    }
+    let length = text.chars().count();
+    if rule.min_length.is_some_and(|min| length < min) {
+        return false;
+    }
+    if rule.max_length.is_some_and(|max| length > max) {
+        return false;
+    }
+    if let Some(regex) = &compiled.regex {
+        if !regex.is_match(text) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str) -> ClassificationRule {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn rejects_rules_without_conditions() {
+        let mut blank = rule("blank");
+        blank.prefix = Some(String::new());
+        blank.tags = vec!["work".to_string()];
+        assert!(validate(&[blank.clone()]).is_err());
+
+        let mut with_regex = blank.clone();
+        with_regex.regex = Some("^TODO".to_string());
+        let mut with_app = rule("app");
+        with_app.source_app = Some("Code.exe".to_string());
+        let mut with_length = rule("length");
+        with_length.max_length = Some(10);
+        assert!(validate(&[with_regex, with_app, with_length]).is_ok());
+    }
+}
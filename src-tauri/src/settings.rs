@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::abbreviations::{self, Abbreviation};
+use crate::clipboard::log_line;
+use crate::paste::PasteMode;
+use crate::paste_input::PasteChord;
+use crate::paste_profiles::PasteProfile;
+use crate::phash::ImageDuplicatePolicy;
+use crate::rules::{self, ClassificationRule};
+
+/// 保存在 app_config_dir/settings.json 中的用户设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub classification_rules: Vec<ClassificationRule>,
+    /// 被禁用的分类器 id，见 classify::Pipeline::with_builtins
+    pub disabled_classifiers: Vec<String>,
+    /// Tesseract 语言，例如 eng 或 eng+chi_sim，默认 eng
+    pub ocr_language: Option<String>,
+    pub image_duplicate_policy: ImageDuplicatePolicy,
+    /// 近似重复的汉明距离阈值（0-64），默认见 phash::DEFAULT_THRESHOLD
+    pub image_duplicate_threshold: Option<u32>,
+    /// 调用粘贴命令时未指定 mode 时使用的粘贴模式
+    pub default_paste_mode: PasteMode,
+    /// 粘贴后恢复粘贴前的剪贴板内容
+    pub restore_clipboard_after_paste: bool,
+    /// 粘贴后等待多久再恢复，默认见 clipboard::DEFAULT_RESTORE_DELAY_MS
+    pub restore_clipboard_delay_ms: Option<u64>,
+    /// 模拟粘贴时按的组合键，终端里一般要用 Ctrl+Shift+V
+    pub paste_chord: PasteChord,
+    /// 逐字输入时每个字符之间的间隔，默认见 paste_input::DEFAULT_TYPE_DELAY_MS
+    pub type_delay_ms: Option<u64>,
+    /// 按目标程序覆盖粘贴组合键、粘贴模式和转换，按顺序取第一个匹配的
+    pub paste_profiles: Vec<PasteProfile>,
+    /// 输入缩写后自动替换，见 abbreviations 模块
+    pub abbreviations: Vec<Abbreviation>,
+}
+
+impl Settings {
+    pub fn classifier_enabled(&self, id: &str) -> bool {
+        !self
+            .disabled_classifiers
+            .iter()
+            .any(|disabled| disabled == id)
+    }
+}
+
+struct SettingsCache {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    settings: Arc<Settings>,
+}
+
+static SETTINGS: OnceLock<Mutex<SettingsCache>> = OnceLock::new();
+
+pub fn init(path: PathBuf) {
+    let modified = modified_time(&path);
+    let settings = read_settings(&path).unwrap_or_default();
+    abbreviations::reload(&settings.abbreviations);
+    let _ = SETTINGS.set(Mutex::new(SettingsCache {
+        path,
+        modified,
+        settings: Arc::new(settings),
+    }));
+}
+
+/// 返回当前设置；文件被外部修改后会自动重新加载
+pub fn current() -> Arc<Settings> {
+    let Some(cache) = SETTINGS.get() else {
+        return Arc::new(Settings::default());
+    };
+    let mut cache = cache.lock().unwrap();
+    let modified = modified_time(&cache.path);
+    if modified != cache.modified {
+        cache.modified = modified;
+        match read_settings(&cache.path) {
+            Some(settings) => {
+                log_line("settings: reloaded settings.json");
+                abbreviations::reload(&settings.abbreviations);
+                cache.settings = Arc::new(settings);
+            }
+            // 解析失败时保留上一次的有效设置，避免编辑到一半时规则全部失效
+            None => log_line("settings: settings.json is invalid, keeping previous settings"),
+        }
+    }
+    cache.settings.clone()
+}
+
+pub fn save(settings: Settings) -> Result<(), String> {
+    abbreviations::validate(&settings.abbreviations)?;
+    rules::validate(&settings.classification_rules)?;
+    let cache = SETTINGS.get().ok_or_else(|| "设置尚未初始化".to_string())?;
+    let mut cache = cache.lock().unwrap();
+    if let Some(dir) = cache.path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&cache.path, json).map_err(|e| e.to_string())?;
+    cache.modified = modified_time(&cache.path);
+    abbreviations::reload(&settings.abbreviations);
+    cache.settings = Arc::new(settings);
+    Ok(())
+}
+
+fn read_settings(path: &PathBuf) -> Option<Settings> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Some(Settings::default()),
+    };
+    match serde_json::from_str(&content) {
+        Ok(settings) => Some(settings),
+        Err(err) => {
+            log_line(&format!("settings: failed to parse settings.json: {err}"));
+            None
+        }
+    }
+}
+
+fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
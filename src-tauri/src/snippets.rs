@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+// {{date}} 未指定格式时使用
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 展开占位符时用到的外部数据
+pub struct ExpandContext<'a> {
+    pub now: DateTime<Local>,
+    /// 粘贴前剪贴板里的文本，替换 {{clipboard}}
+    pub clipboard: Option<&'a str>,
+    /// {{input:名称}} 对应的用户输入
+    pub inputs: &'a HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Expansion {
+    pub text: String,
+    /// 模板中有 {{cursor}} 时，粘贴后需要按多少次左方向键把光标移回去
+    pub cursor_offset: Option<usize>,
+}
+
+enum Placeholder<'a> {
+    Date(&'a str),
+    Clipboard,
+    Cursor,
+    Input(&'a str),
+}
+
+impl<'a> Placeholder<'a> {
+    fn parse(inner: &'a str) -> Option<Self> {
+        let (name, arg) = match inner.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg)),
+            None => (inner.trim(), None),
+        };
+        match (name, arg) {
+            ("date", format) => Some(Self::Date(
+                format
+                    .filter(|format| !format.is_empty())
+                    .unwrap_or(DEFAULT_DATE_FORMAT),
+            )),
+            ("clipboard", None) => Some(Self::Clipboard),
+            ("cursor", None) => Some(Self::Cursor),
+            ("input", Some(name)) if !name.trim().is_empty() => Some(Self::Input(name.trim())),
+            _ => None,
+        }
+    }
+}
+
+/// 依次找出模板里的 {{...}}，返回占位符前的文本、占位符原文和内部文本
+fn placeholders(template: &str) -> impl Iterator<Item = (&str, &str, &str)> + '_ {
+    let mut rest = template;
+    std::iter::from_fn(move || {
+        let start = rest.find("{{")?;
+        let len = rest[start + 2..].find("}}")?;
+        let end = start + 2 + len + 2;
+        let item = (&rest[..start], &rest[start..end], &rest[start + 2..end - 2]);
+        rest = &rest[end..];
+        Some(item)
+    })
+}
+
+/// 展开模板；不认识的 {{...}} 原样保留，片段内容本身可能就是别的模板语言
+pub fn expand(template: &str, context: &ExpandContext) -> Result<Expansion, String> {
+    let mut text = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut consumed = 0;
+    for (before, raw, inner) in placeholders(template) {
+        text.push_str(before);
+        consumed += before.len() + raw.len();
+        match Placeholder::parse(inner) {
+            Some(Placeholder::Date(format)) => text.push_str(&format_date(context.now, format)?),
+            Some(Placeholder::Clipboard) => text.push_str(context.clipboard.unwrap_or_default()),
+            // 只有第一个 {{cursor}} 生效
+            Some(Placeholder::Cursor) => {
+                cursor.get_or_insert(text.len());
+            }
+            Some(Placeholder::Input(name)) => {
+                let value = context
+                    .inputs
+                    .get(name)
+                    .ok_or_else(|| format!("缺少输入项: {name}"))?;
+                text.push_str(value);
+            }
+            None => text.push_str(raw),
+        }
+    }
+    text.push_str(&template[consumed..]);
+    let cursor_offset = cursor.map(|index| keystrokes(&text[index..]));
+    Ok(Expansion {
+        text,
+        cursor_offset,
+    })
+}
+
+/// 模板中 {{input:名称}} 的名称，按出现顺序去重
+pub fn input_names(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, _, inner) in placeholders(template) {
+        if let Some(Placeholder::Input(name)) = Placeholder::parse(inner) {
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+fn format_date(now: DateTime<Local>, format: &str) -> Result<String, String> {
+    // 无效的格式在 Display 时会 panic，先解析一遍
+    let items = StrftimeItems::new(format)
+        .parse()
+        .map_err(|_| format!("日期格式无效: {format}"))?;
+    Ok(now.format_with_items(items.iter()).to_string())
+}
+
+// 光标后的文本需要按几次左方向键，\r\n 只算一次
+fn keystrokes(text: &str) -> usize {
+    text.chars().count() - text.matches("\r\n").count()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn expand_with(template: &str, inputs: &[(&str, &str)]) -> Result<Expansion, String> {
+        let inputs: HashMap<String, String> = inputs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let context = ExpandContext {
+            now: Local.with_ymd_and_hms(2024, 3, 5, 9, 7, 0).unwrap(),
+            clipboard: Some("copied"),
+            inputs: &inputs,
+        };
+        expand(template, &context)
+    }
+
+    #[test]
+    fn expands_date_formats() {
+        let expansion = expand_with("{{date}} {{date:%H:%M}} {{date:%d/%m}}", &[]).unwrap();
+        assert_eq!(expansion.text, "2024-03-05 09:07 05/03");
+        assert_eq!(expansion.cursor_offset, None);
+    }
+
+    #[test]
+    fn invalid_date_format_is_error() {
+        assert!(expand_with("{{date:%Q}}", &[]).is_err());
+    }
+
+    #[test]
+    fn expands_clipboard_and_inputs() {
+        let expansion =
+            expand_with("Hi {{input:name}}, {{clipboard}}", &[("name", "Ann")]).unwrap();
+        assert_eq!(expansion.text, "Hi Ann, copied");
+    }
+
+    #[test]
+    fn missing_input_is_error() {
+        let err = expand_with("{{input:name}}", &[]).unwrap_err();
+        assert!(err.contains("name"));
+    }
+
+    #[test]
+    fn unknown_placeholders_kept_verbatim() {
+        let expansion = expand_with("{{foo}} {{ clipboard:x }} {{input:}} {{open", &[]).unwrap();
+        assert_eq!(
+            expansion.text,
+            "{{foo}} {{ clipboard:x }} {{input:}} {{open"
+        );
+    }
+
+    #[test]
+    fn only_first_cursor_counts() {
+        let expansion = expand_with("a{{cursor}}bc{{cursor}}dé", &[]).unwrap();
+        assert_eq!(expansion.text, "abcdé");
+        assert_eq!(expansion.cursor_offset, Some(4));
+    }
+
+    #[test]
+    fn cursor_offset_counts_crlf_once() {
+        let expansion = expand_with("<p>{{cursor}}</p>\r\nend", &[]).unwrap();
+        assert_eq!(expansion.cursor_offset, Some(8));
+        let expansion = expand_with("x{{cursor}}", &[]).unwrap();
+        assert_eq!(expansion.cursor_offset, Some(0));
+    }
+
+    #[test]
+    fn input_names_deduped_in_order() {
+        let names = input_names("{{input:b}} {{input:a}} {{input: b }} {{date}} {{input:c}}");
+        assert_eq!(names, ["b", "a", "c"]);
+    }
+}
@@ -0,0 +1,526 @@
+use std::io::Cursor;
+
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+// 常见的 HTML 根标签，用于区分 HTML 片段和 XML 文档
+const HTML_TAGS: &[&str] = &[
+    "html", "head", "body", "div", "span", "p", "a", "ul", "ol", "li", "table", "tr", "td", "th",
+    "img", "br", "h1", "h2", "h3", "h4", "h5", "h6", "section", "article", "header", "footer",
+    "nav", "main", "form", "input", "button", "pre", "code", "strong", "em", "b", "i", "meta",
+    "link", "script", "style",
+];
+
+/// 结构化数据的识别结果
+pub struct StructuredInfo {
+    pub format: &'static str,
+    pub valid: bool,
+    pub shape: Value,
+}
+
+/// 重新复制结构化数据时的输出形式
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StructuredOutput {
+    Pretty,
+    Minified,
+    Json,
+    Yaml,
+}
+
+/// 识别 JSON / YAML / XML / CSV，并用对应的解析器校验
+pub fn detect_structured(text: &str, language: Option<&str>) -> Option<StructuredInfo> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let json_like = (trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']'));
+    if json_like {
+        match serde_json::from_str::<Value>(trimmed) {
+            Ok(value) => {
+                return Some(StructuredInfo {
+                    format: "json",
+                    valid: true,
+                    shape: value_shape(&value),
+                })
+            }
+            // 花括号包起来的代码块交给代码识别，只有看不出语言时才算作损坏的 JSON
+            Err(err) if language.is_none() => return Some(invalid("json", err.to_string())),
+            Err(_) => {}
+        }
+    }
+
+    if looks_like_xml(trimmed) {
+        return Some(match xml_shape(trimmed) {
+            Ok(shape) => StructuredInfo {
+                format: "xml",
+                valid: true,
+                shape,
+            },
+            Err(err) => invalid("xml", err),
+        });
+    }
+
+    if language == Some("yaml") || trimmed.starts_with("---\n") {
+        return Some(match serde_yaml::from_str::<serde_yaml::Value>(trimmed) {
+            Ok(value) => StructuredInfo {
+                format: "yaml",
+                valid: true,
+                shape: serde_json::to_value(&value)
+                    .map(|value| value_shape(&value))
+                    .unwrap_or(Value::Null),
+            },
+            Err(err) => invalid("yaml", err.to_string()),
+        });
+    }
+
+    // 其他语言的代码（比如 SQL 里的逗号）不当作 CSV
+    if language.is_none() {
+        if let Some(delimiter) = guess_csv_delimiter(trimmed) {
+            return Some(match csv_shape(trimmed, delimiter) {
+                Ok(shape) => StructuredInfo {
+                    format: "csv",
+                    valid: true,
+                    shape,
+                },
+                Err(err) => invalid("csv", err),
+            });
+        }
+    }
+
+    None
+}
+
+fn invalid(format: &'static str, error: String) -> StructuredInfo {
+    StructuredInfo {
+        format,
+        valid: false,
+        shape: json!({ "error": error }),
+    }
+}
+
+fn value_shape(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => json!({ "kind": "object", "keys": map.len() }),
+        Value::Array(items) => json!({ "kind": "array", "items": items.len() }),
+        _ => json!({ "kind": "scalar" }),
+    }
+}
+
+fn looks_like_xml(text: &str) -> bool {
+    if text.starts_with("<?xml") {
+        return true;
+    }
+    if !text.starts_with('<') || !text.ends_with('>') {
+        return false;
+    }
+    let name: String = text[1..]
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, ':' | '-' | '_' | '.'))
+        .collect();
+    !name.is_empty() && !HTML_TAGS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+fn xml_shape(text: &str) -> Result<Value, String> {
+    let mut reader = Reader::from_str(text);
+    let mut root: Option<String> = None;
+    let mut roots = 0usize;
+    let mut elements = 0usize;
+    let mut depth = 0usize;
+    loop {
+        match reader.read_event().map_err(|err| err.to_string())? {
+            Event::Start(start) => {
+                if depth == 0 {
+                    roots += 1;
+                    root.get_or_insert_with(|| {
+                        String::from_utf8_lossy(start.name().as_ref()).into_owned()
+                    });
+                }
+                depth += 1;
+                elements += 1;
+            }
+            Event::Empty(empty) => {
+                if depth == 0 {
+                    roots += 1;
+                    root.get_or_insert_with(|| {
+                        String::from_utf8_lossy(empty.name().as_ref()).into_owned()
+                    });
+                }
+                elements += 1;
+            }
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Text(text) if depth == 0 && !text.iter().all(u8::is_ascii_whitespace) => {
+                return Err("根元素之外存在文本".to_string());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err("存在未闭合的元素".to_string());
+    }
+    if roots != 1 {
+        return Err(format!("应当只有一个根元素，实际为 {roots} 个"));
+    }
+    Ok(json!({ "root": root, "elements": elements }))
+}
+
+fn guess_csv_delimiter(text: &str) -> Option<u8> {
+    let lines = text.lines().filter(|l| !l.trim().is_empty()).count();
+    if lines < 2 {
+        return None;
+    }
+    [b'\t', b',', b';', b'|'].into_iter().find(|&delimiter| {
+        // 两行带逗号的普通文字太常见，逗号类分隔符至少要三行
+        if delimiter != b'\t' && lines < 3 {
+            return false;
+        }
+        let widths: Vec<usize> = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+            .records()
+            .map_while(Result::ok)
+            .map(|record| record.len())
+            .collect();
+        let Some(&first) = widths.first() else {
+            return false;
+        };
+        // 大部分行列数一致才认为是表格，个别行不一致时按损坏的 CSV 处理
+        let consistent = widths.iter().filter(|&&width| width == first).count();
+        first > 1 && consistent * 10 >= widths.len() * 8
+    })
+}
+
+fn csv_shape(text: &str, delimiter: u8) -> Result<Value, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(false)
+        .from_reader(text.as_bytes());
+    let mut rows = 0usize;
+    let mut columns = 0usize;
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        columns = record.len();
+        rows += 1;
+    }
+    Ok(json!({
+        "rows": rows,
+        "columns": columns,
+        "delimiter": (delimiter as char).to_string(),
+    }))
+}
+
+/// 按指定形式重新输出结构化数据
+pub fn convert(text: &str, format: &str, output: StructuredOutput) -> Result<String, String> {
+    let trimmed = text.trim();
+    match (format, output) {
+        ("json", StructuredOutput::Pretty | StructuredOutput::Json) => {
+            let value = parse_json(trimmed)?;
+            serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+        }
+        ("json", StructuredOutput::Minified) => {
+            let value = parse_json(trimmed)?;
+            serde_json::to_string(&value).map_err(|err| err.to_string())
+        }
+        ("json", StructuredOutput::Yaml) => {
+            let value = parse_json(trimmed)?;
+            serde_yaml::to_string(&value).map_err(|err| err.to_string())
+        }
+        ("yaml", StructuredOutput::Pretty | StructuredOutput::Yaml) => {
+            let value = parse_yaml(trimmed)?;
+            serde_yaml::to_string(&value).map_err(|err| err.to_string())
+        }
+        ("yaml", StructuredOutput::Json) => {
+            let value = parse_yaml(trimmed)?;
+            serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+        }
+        // JSON 是 YAML 的子集，压缩后的 YAML 直接输出单行 JSON
+        ("yaml", StructuredOutput::Minified) => {
+            let value = parse_yaml(trimmed)?;
+            serde_json::to_string(&value).map_err(|err| err.to_string())
+        }
+        ("xml", StructuredOutput::Pretty) => reformat_xml(trimmed, true),
+        ("xml", StructuredOutput::Minified) => reformat_xml(trimmed, false),
+        ("csv", StructuredOutput::Json) => {
+            let delimiter = guess_csv_delimiter(trimmed).unwrap_or(b',');
+            let rows = csv_to_json(trimmed, delimiter)?;
+            serde_json::to_string_pretty(&rows).map_err(|err| err.to_string())
+        }
+        ("csv", StructuredOutput::Yaml) => {
+            let delimiter = guess_csv_delimiter(trimmed).unwrap_or(b',');
+            let rows = csv_to_json(trimmed, delimiter)?;
+            serde_yaml::to_string(&rows).map_err(|err| err.to_string())
+        }
+        ("csv", StructuredOutput::Pretty) => {
+            let delimiter = guess_csv_delimiter(trimmed).unwrap_or(b',');
+            reformat_csv(trimmed, delimiter, true)
+        }
+        ("csv", StructuredOutput::Minified) => {
+            let delimiter = guess_csv_delimiter(trimmed).unwrap_or(b',');
+            reformat_csv(trimmed, delimiter, false)
+        }
+        _ => Err(format!("不支持将 {format} 转换为该格式")),
+    }
+}
+
+fn parse_json(text: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|err| format!("JSON 解析失败: {err}"))
+}
+
+fn parse_yaml(text: &str) -> Result<Value, String> {
+    serde_yaml::from_str(text).map_err(|err| format!("YAML 解析失败: {err}"))
+}
+
+// Pretty 按列补空格对齐，Minified 去掉字段两侧的空白；重新输出时只在需要时加引号
+fn reformat_csv(text: &str, delimiter: u8, align: bool) -> Result<String, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| format!("CSV 解析失败: {err}"))?;
+        rows.push(
+            record
+                .iter()
+                .map(|field| quote_csv_field(field, delimiter))
+                .collect(),
+        );
+    }
+
+    let mut widths: Vec<usize> = Vec::new();
+    if align {
+        for row in &rows {
+            for (index, field) in row.iter().enumerate() {
+                if widths.len() <= index {
+                    widths.push(0);
+                }
+                widths[index] = widths[index].max(field.chars().count());
+            }
+        }
+    }
+    let separator = delimiter as char;
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (index, field) in row.iter().enumerate() {
+                if index > 0 {
+                    line.push(separator);
+                    if align {
+                        line.push(' ');
+                    }
+                }
+                line.push_str(field);
+                // 最后一列不补空格
+                if align && index + 1 < row.len() {
+                    let padding = widths[index] - field.chars().count();
+                    line.extend(std::iter::repeat_n(' ', padding));
+                }
+            }
+            line
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+fn quote_csv_field(field: &str, delimiter: u8) -> String {
+    if field.contains([delimiter as char, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// 第一行作为表头，转换成对象数组
+fn csv_to_json(text: &str, delimiter: u8) -> Result<Value, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|err| format!("CSV 解析失败: {err}"))?
+        .clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| format!("CSV 解析失败: {err}"))?;
+        let row: serde_json::Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+fn reformat_xml(text: &str, pretty: bool) -> Result<String, String> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut writer = if pretty {
+        Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2)
+    } else {
+        Writer::new(Cursor::new(Vec::new()))
+    };
+    loop {
+        match reader
+            .read_event()
+            .map_err(|err| format!("XML 解析失败: {err}"))?
+        {
+            Event::Eof => break,
+            event => writer
+                .write_event(event)
+                .map_err(|err| format!("XML 输出失败: {err}"))?,
+        }
+    }
+    String::from_utf8(writer.into_inner().into_inner()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Option<(&'static str, bool, Value)> {
+        detect_structured(text, None).map(|info| (info.format, info.valid, info.shape))
+    }
+
+    #[test]
+    fn detects_well_formed_input() {
+        let cases = [
+            (
+                r#"{"a": 1, "b": [1, 2]}"#,
+                "json",
+                json!({ "kind": "object", "keys": 2 }),
+            ),
+            ("[1, 2, 3]", "json", json!({ "kind": "array", "items": 3 })),
+            (
+                "<?xml version=\"1.0\"?>\n<root><a/><b>x</b></root>",
+                "xml",
+                json!({ "root": "root", "elements": 3 }),
+            ),
+            (
+                "<config><item/></config>",
+                "xml",
+                json!({ "root": "config", "elements": 2 }),
+            ),
+            (
+                "---\na: 1\nb:\n  - x\n",
+                "yaml",
+                json!({ "kind": "object", "keys": 2 }),
+            ),
+            (
+                "name,age\nann,30\nbob,40",
+                "csv",
+                json!({ "rows": 3, "columns": 2, "delimiter": "," }),
+            ),
+            (
+                "a\tb\tc\n1\t2\t3",
+                "csv",
+                json!({ "rows": 2, "columns": 3, "delimiter": "\t" }),
+            ),
+            (
+                "a;b\n1;2\n3;4\n5;6",
+                "csv",
+                json!({ "rows": 4, "columns": 2, "delimiter": ";" }),
+            ),
+        ];
+        for (text, format, shape) in cases {
+            assert_eq!(detect(text), Some((format, true, shape)), "{text}");
+        }
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        let cases = [
+            (r#"{"a": 1,}"#, "json"),
+            ("[1, 2,]", "json"),
+            ("<root><a></root>", "xml"),
+            ("<x/><y/>", "xml"),
+            ("---\na: [1, 2\n", "yaml"),
+            ("a,b,c\n1,2,3\n4,5,6\n7,8,9\n10,11", "csv"),
+        ];
+        for (text, format) in cases {
+            let (detected, valid, shape) = detect(text).unwrap_or_else(|| panic!("{text}"));
+            assert_eq!((detected, valid), (format, false), "{text}");
+            assert!(shape["error"].is_string(), "{text}");
+        }
+    }
+
+    #[test]
+    fn ignores_plain_text_and_code() {
+        assert!(detect("hello, world\nsecond line").is_none());
+        assert!(detect("<div>html</div>").is_none());
+        assert!(detect_structured("{ let x = 1; }", Some("rust")).is_none());
+        assert!(detect_structured("a,b\nc,d\ne,f", Some("sql")).is_none());
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let json = r#"{"b": [1, 2], "a": "x"}"#;
+        let yaml = "a: x\nb:\n- 1\n- 2\n";
+        let csv = "name, age\nann,30\n\"b, c\",4";
+        let cases = [
+            (json, "json", StructuredOutput::Minified, r#"{"a":"x","b":[1,2]}"#),
+            (
+                json,
+                "json",
+                StructuredOutput::Pretty,
+                "{\n  \"a\": \"x\",\n  \"b\": [\n    1,\n    2\n  ]\n}",
+            ),
+            (json, "json", StructuredOutput::Yaml, yaml),
+            (yaml, "yaml", StructuredOutput::Json, "{\n  \"a\": \"x\",\n  \"b\": [\n    1,\n    2\n  ]\n}"),
+            (yaml, "yaml", StructuredOutput::Minified, r#"{"a":"x","b":[1,2]}"#),
+            (yaml, "yaml", StructuredOutput::Pretty, yaml),
+            (
+                "<a>\n  <b> x </b>\n</a>",
+                "xml",
+                StructuredOutput::Minified,
+                "<a><b>x</b></a>",
+            ),
+            ("<a><b>x</b></a>", "xml", StructuredOutput::Pretty, "<a>\n  <b>x</b>\n</a>"),
+            (
+                csv,
+                "csv",
+                StructuredOutput::Json,
+                "[\n  {\n    \" age\": \"30\",\n    \"name\": \"ann\"\n  },\n  {\n    \" age\": \"4\",\n    \"name\": \"b, c\"\n  }\n]",
+            ),
+            (
+                "a,b\n1,2\n3,4",
+                "csv",
+                StructuredOutput::Yaml,
+                "- a: '1'\n  b: '2'\n- a: '3'\n  b: '4'\n",
+            ),
+            (csv, "csv", StructuredOutput::Minified, "name,age\nann,30\n\"b, c\",4"),
+            (
+                csv,
+                "csv",
+                StructuredOutput::Pretty,
+                "name  , age\nann   , 30\n\"b, c\", 4",
+            ),
+        ];
+        for (text, format, output, expected) in cases {
+            assert_eq!(
+                convert(text, format, output).unwrap(),
+                expected,
+                "{format} {output:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn conversion_errors() {
+        assert!(convert("{bad", "json", StructuredOutput::Pretty).is_err());
+        assert!(convert("a: [", "yaml", StructuredOutput::Json).is_err());
+        assert!(convert("<a><b></a>", "xml", StructuredOutput::Pretty).is_err());
+        assert!(convert("<a/>", "xml", StructuredOutput::Json).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+// RFC 3986 中不需要编码的字符
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// 粘贴前对文本做的一次转换，多个转换按顺序执行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Transform {
+    /// 去掉首尾空白
+    Trim,
+    /// 去掉每一行首尾的空白
+    TrimLines,
+    RemoveEmptyLines,
+    Uppercase,
+    Lowercase,
+    /// 每个单词首字母大写
+    TitleCase,
+    /// 合并连续空白为一个空格
+    CollapseWhitespace,
+    UrlEncode,
+    UrlDecode,
+    Base64Encode,
+    Base64Decode,
+    /// 转成 JSON 字符串字面量（带引号）
+    JsonEscape,
+    /// 把 JSON 字符串字面量还原成文本
+    JsonUnescape,
+    /// 用单引号包起来，可以直接作为 shell 参数
+    ShellEscape,
+    #[serde(rename_all = "camelCase")]
+    SortLines {
+        #[serde(default)]
+        descending: bool,
+        #[serde(default)]
+        case_insensitive: bool,
+    },
+    ReverseLines,
+    /// 去掉重复行，保留第一次出现的顺序
+    DedupeLines,
+}
+
+pub fn apply_all(text: &str, transforms: &[Transform]) -> Result<String, String> {
+    let mut text = text.to_string();
+    for transform in transforms {
+        text = apply(&text, transform)?;
+    }
+    Ok(text)
+}
+
+pub fn apply(text: &str, transform: &Transform) -> Result<String, String> {
+    let output = match transform {
+        Transform::Trim => text.trim().to_string(),
+        Transform::TrimLines => {
+            map_lines(text, |lines| lines.iter().map(|line| line.trim()).collect())
+        }
+        Transform::RemoveEmptyLines => map_lines(text, |lines| {
+            lines
+                .iter()
+                .copied()
+                .filter(|line| !line.trim().is_empty())
+                .collect()
+        }),
+        Transform::Uppercase => text.to_uppercase(),
+        Transform::Lowercase => text.to_lowercase(),
+        Transform::TitleCase => title_case(text),
+        Transform::CollapseWhitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        Transform::UrlEncode => utf8_percent_encode(text, URL_COMPONENT).to_string(),
+        Transform::UrlDecode => {
+            // 表单编码里的 + 表示空格
+            let text = text.replace('+', " ");
+            percent_decode_str(&text)
+                .decode_utf8()
+                .map_err(|_| "URL 解码后不是有效的 UTF-8 文本".to_string())?
+                .into_owned()
+        }
+        Transform::Base64Encode => STANDARD.encode(text.as_bytes()),
+        Transform::Base64Decode => base64_decode(text)?,
+        Transform::JsonEscape => serde_json::to_string(text).map_err(|err| err.to_string())?,
+        Transform::JsonUnescape => json_unescape(text)?,
+        Transform::ShellEscape => format!("'{}'", text.replace('\'', r"'\''")),
+        Transform::SortLines {
+            descending,
+            case_insensitive,
+        } => map_lines(text, |lines| {
+            let mut lines = lines.to_vec();
+            if *case_insensitive {
+                lines.sort_by_cached_key(|line| line.to_lowercase());
+            } else {
+                lines.sort();
+            }
+            if *descending {
+                lines.reverse();
+            }
+            lines
+        }),
+        Transform::ReverseLines => map_lines(text, |lines| lines.iter().rev().copied().collect()),
+        Transform::DedupeLines => map_lines(text, |lines| {
+            let mut seen = std::collections::HashSet::new();
+            lines
+                .iter()
+                .copied()
+                .filter(|line| seen.insert(*line))
+                .collect()
+        }),
+    };
+    Ok(output)
+}
+
+// 按行处理，保留原文的换行符风格和末尾换行
+fn map_lines<'a>(text: &'a str, f: impl FnOnce(&[&'a str]) -> Vec<&'a str>) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let trailing = text.ends_with('\n');
+    let lines: Vec<&str> = text.lines().collect();
+    let mut output = f(&lines).join(newline);
+    if trailing {
+        output.push_str(newline);
+    }
+    output
+}
+
+fn title_case(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() || ch == '\'' {
+            if at_word_start {
+                output.extend(ch.to_uppercase());
+            } else {
+                output.extend(ch.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            output.push(ch);
+            at_word_start = true;
+        }
+    }
+    output
+}
+
+fn base64_decode(text: &str) -> Result<String, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    // 兼容 URL 安全字符集和省略了填充的写法
+    let bytes = STANDARD
+        .decode(&compact)
+        .or_else(|_| URL_SAFE_NO_PAD.decode(compact.trim_end_matches('=')))
+        .map_err(|_| "不是有效的 Base64 文本".to_string())?;
+    String::from_utf8(bytes).map_err(|_| "Base64 解码后不是文本".to_string())
+}
+
+fn json_unescape(text: &str) -> Result<String, String> {
+    let trimmed = text.trim();
+    let quoted = if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() >= 2 {
+        trimmed.to_string()
+    } else {
+        format!("\"{trimmed}\"")
+    };
+    serde_json::from_str::<String>(&quoted).map_err(|err| format!("不是有效的 JSON 字符串: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, transform: Transform) -> String {
+        apply(text, &transform).unwrap()
+    }
+
+    #[test]
+    fn trims_text_and_lines() {
+        assert_eq!(run("  a b \n", Transform::Trim), "a b");
+        assert_eq!(run(" a \n\tb\t\n", Transform::TrimLines), "a\nb\n");
+        assert_eq!(run("a\n\n  \nb", Transform::RemoveEmptyLines), "a\nb");
+    }
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(run("Straße ok", Transform::Uppercase), "STRASSE OK");
+        assert_eq!(run("ÄBC", Transform::Lowercase), "äbc");
+        assert_eq!(
+            run("hello wORLD, it's x-ray", Transform::TitleCase),
+            "Hello World, It's X-Ray"
+        );
+        assert_eq!(run(" a \t b\n\nc ", Transform::CollapseWhitespace), "a b c");
+    }
+
+    #[test]
+    fn url_round_trip() {
+        let encoded = run("a b/c?d=é~", Transform::UrlEncode);
+        assert_eq!(encoded, "a%20b%2Fc%3Fd%3D%C3%A9~");
+        assert_eq!(run(&encoded, Transform::UrlDecode), "a b/c?d=é~");
+    }
+
+    #[test]
+    fn url_decode_treats_plus_as_space() {
+        assert_eq!(run("a+b%2Bc", Transform::UrlDecode), "a b+c");
+        assert!(apply("%FF", &Transform::UrlDecode).is_err());
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let encoded = run("hello?>", Transform::Base64Encode);
+        assert_eq!(encoded, "aGVsbG8/Pg==");
+        assert_eq!(run(&encoded, Transform::Base64Decode), "hello?>");
+    }
+
+    #[test]
+    fn base64_decodes_url_safe_and_unpadded() {
+        assert_eq!(run("aGVsbG8_Pg", Transform::Base64Decode), "hello?>");
+        assert_eq!(run("aGVsbG8_Pg==", Transform::Base64Decode), "hello?>");
+        assert_eq!(run("aGk", Transform::Base64Decode), "hi");
+        assert_eq!(run("aGVs\nbG8=", Transform::Base64Decode), "hello");
+        assert!(apply("not base64!", &Transform::Base64Decode).is_err());
+        assert!(apply("/w==", &Transform::Base64Decode).is_err());
+    }
+
+    #[test]
+    fn json_escape_round_trip() {
+        let escaped = run("say \"hi\"\n\\", Transform::JsonEscape);
+        assert_eq!(escaped, r#""say \"hi\"\n\\""#);
+        assert_eq!(run(&escaped, Transform::JsonUnescape), "say \"hi\"\n\\");
+    }
+
+    #[test]
+    fn json_unescape_without_quotes() {
+        assert_eq!(run(r"a\tbé", Transform::JsonUnescape), "a\tbé");
+        assert_eq!(run(r#"  "x"  "#, Transform::JsonUnescape), "x");
+        assert!(apply(r"bad\q", &Transform::JsonUnescape).is_err());
+    }
+
+    #[test]
+    fn shell_escape_embedded_quote() {
+        assert_eq!(run("it's", Transform::ShellEscape), r"'it'\''s'");
+        assert_eq!(run("$HOME", Transform::ShellEscape), "'$HOME'");
+    }
+
+    #[test]
+    fn sort_lines_flags() {
+        let sort = |descending, case_insensitive| Transform::SortLines {
+            descending,
+            case_insensitive,
+        };
+        let text = "b\nA\nc\na";
+        assert_eq!(run(text, sort(false, false)), "A\na\nb\nc");
+        assert_eq!(run(text, sort(true, false)), "c\nb\na\nA");
+        assert_eq!(run(text, sort(false, true)), "A\na\nb\nc");
+        assert_eq!(run("b\na\nA", sort(false, true)), "a\nA\nb");
+        assert_eq!(run("b\na\nA", sort(true, true)), "b\nA\na");
+    }
+
+    #[test]
+    fn reverses_and_dedupes_lines() {
+        assert_eq!(run("1\n2\n3", Transform::ReverseLines), "3\n2\n1");
+        assert_eq!(run("b\na\nb\nc\na", Transform::DedupeLines), "b\na\nc");
+    }
+
+    #[test]
+    fn map_lines_keeps_crlf_and_trailing_newline() {
+        assert_eq!(run("b\r\na\r\n", Transform::ReverseLines), "a\r\nb\r\n");
+        assert_eq!(run("b\na", Transform::ReverseLines), "a\nb");
+        assert_eq!(run(" x \r\n y ", Transform::TrimLines), "x\r\ny");
+    }
+
+    #[test]
+    fn apply_all_runs_in_order() {
+        let transforms = [
+            Transform::Trim,
+            Transform::Uppercase,
+            Transform::Base64Encode,
+        ];
+        assert_eq!(apply_all("  hi ", &transforms).unwrap(), "SEk=");
+        assert!(apply_all("x", &[Transform::Base64Decode]).is_err());
+    }
+}
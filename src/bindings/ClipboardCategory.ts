@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 界面上按类别筛选用的分类
+ */
+export type ClipboardCategory = "link" | "image" | "text" | "file" | "code" | "data";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 剪贴板内容的存储格式，决定重新写回剪贴板时使用哪种数据
+ *
+ * 取值会写进数据库的 CHECK 约束，新增取值时需要在 db 中增加一次重建表的迁移
+ */
+export type ClipboardFormat = "text" | "image" | "html" | "file" | "color";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 从文本中提取的实体类型，存放在 item_entities 表
+ */
+export type EntityKind = "email" | "phone" | "address";
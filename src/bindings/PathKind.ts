@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 路径检查时的类型，不存在的路径为空
+ */
+export type PathKind = "file" | "directory";
@@ -1,4 +1,27 @@
-import type { ClipboardItem } from "@/types";
+import type {
+  ClassifierInfo,
+  ClipboardItem,
+  ColorNotation,
+  Entity,
+  EntityKind,
+  EntityKindCount,
+  ImageExportSummary,
+  ImageFileFormat,
+  ImageOp,
+  LanguageCount,
+  LinkHostCount,
+  MergeSeparator,
+  PasteMode,
+  PathEntry,
+  QueueOrder,
+  QueueStatus,
+  Settings,
+  Snippet,
+  SnippetExpansion,
+  SnippetInput,
+  StructuredOutput,
+  Transform
+} from "@/types";
 import { invoke } from "@tauri-apps/api/core";
 import { isEnabled, enable, disable } from "@tauri-apps/plugin-autostart";
 
@@ -29,22 +52,251 @@ export async function fetchHistoryByDate(
   }
 }
 
-export async function setClipboard(id: number): Promise<void> {
+export async function fetchLinkHosts(): Promise<LinkHostCount[]> {
   try {
-    await invoke<void>("set_clipboard", { id });
+    return await invoke<LinkHostCount[]>("list_link_hosts");
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchHistoryByLinkHost(host: string, limit = 200): Promise<ClipboardItem[]> {
+  try {
+    return await invoke<ClipboardItem[]>("list_history_by_link_host", { host, limit });
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchLanguages(): Promise<LanguageCount[]> {
+  try {
+    return await invoke<LanguageCount[]>("list_languages");
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchHistoryByLanguage(language: string, limit = 200): Promise<ClipboardItem[]> {
+  try {
+    return await invoke<ClipboardItem[]>("list_history_by_language", { language, limit });
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchEntityKinds(): Promise<EntityKindCount[]> {
+  try {
+    return await invoke<EntityKindCount[]>("list_entity_kinds");
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchItemEntities(id: number): Promise<Entity[]> {
+  try {
+    return await invoke<Entity[]>("list_item_entities", { id });
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchHistoryByEntity(
+  kind: EntityKind | null,
+  query = "",
+  limit = 200
+): Promise<ClipboardItem[]> {
+  try {
+    return await invoke<ClipboardItem[]>("list_history_by_entity", { kind, query, limit });
+  } catch {
+    return [];
+  }
+}
+
+export async function fetchItemPaths(id: number): Promise<PathEntry[]> {
+  try {
+    return await invoke<PathEntry[]>("list_item_paths", { id });
+  } catch {
+    return [];
+  }
+}
+
+export async function findSimilarImages(
+  id: number,
+  threshold?: number,
+  limit = 50
+): Promise<ClipboardItem[]> {
+  try {
+    return await invoke<ClipboardItem[]>("find_similar_images", { id, threshold, limit });
+  } catch {
+    return [];
+  }
+}
+
+export async function editImage(id: number, operations: ImageOp[]): Promise<ClipboardItem> {
+  return await invoke<ClipboardItem>("edit_image", { id, operations });
+}
+
+export async function saveImage(
+  id: number,
+  path: string,
+  format?: ImageFileFormat,
+  quality?: number
+): Promise<void> {
+  await invoke<void>("save_image", { id, path, format, quality });
+}
+
+export async function exportImages(
+  startTs: number,
+  endTs: number,
+  directory: string,
+  format: ImageFileFormat = "png",
+  quality?: number
+): Promise<ImageExportSummary> {
+  return await invoke<ImageExportSummary>("export_images", {
+    startTs,
+    endTs,
+    directory,
+    format,
+    quality
+  });
+}
+
+export async function importImageFile(path: string): Promise<ClipboardItem> {
+  return await invoke<ClipboardItem>("import_image_file", { path });
+}
+
+export async function isOcrAvailable(): Promise<boolean> {
+  try {
+    return await invoke<boolean>("ocr_available");
+  } catch {
+    return false;
+  }
+}
+
+export async function revealPath(path: string): Promise<void> {
+  await invoke<void>("reveal_path", { path });
+}
+
+export async function setClipboard(id: number, mode?: PasteMode): Promise<void> {
+  try {
+    await invoke<void>("set_clipboard", { id, mode });
   } catch {
     // ignore in web context
   }
 }
 
-export async function setClipboardAndPaste(id: number): Promise<void> {
+export async function setClipboardAndPaste(
+  id: number,
+  mode?: PasteMode,
+  restore?: boolean
+): Promise<void> {
   try {
-    await invoke<void>("set_clipboard_and_paste", { id });
+    await invoke<void>("set_clipboard_and_paste", { id, mode, restore });
   } catch {
     // ignore in web context
   }
 }
 
+export async function listSnippets(query?: string): Promise<Snippet[]> {
+  try {
+    return await invoke<Snippet[]>("list_snippets", { query });
+  } catch {
+    return [];
+  }
+}
+
+export async function createSnippet(snippet: SnippetInput): Promise<Snippet> {
+  return await invoke<Snippet>("create_snippet", { snippet });
+}
+
+export async function updateSnippet(id: number, snippet: SnippetInput): Promise<Snippet> {
+  return await invoke<Snippet>("update_snippet", { id, snippet });
+}
+
+export async function deleteSnippet(id: number): Promise<void> {
+  await invoke<void>("delete_snippet", { id });
+}
+
+export async function expandSnippet(
+  id: number,
+  inputs?: Record<string, string>
+): Promise<SnippetExpansion> {
+  return await invoke<SnippetExpansion>("expand_snippet", { id, inputs });
+}
+
+export async function pasteSnippet(
+  id: number,
+  inputs?: Record<string, string>,
+  restore?: boolean
+): Promise<void> {
+  await invoke<void>("set_clipboard_and_paste", { snippetId: id, inputs, restore });
+}
+
+export async function getPasteTargetApp(): Promise<string | null> {
+  try {
+    return await invoke<string | null>("get_paste_target_app");
+  } catch {
+    return null;
+  }
+}
+
+export async function typeClipboardItem(
+  id: number,
+  mode?: PasteMode,
+  delayMs?: number
+): Promise<void> {
+  await invoke<void>("type_clipboard_item", { id, mode, delayMs });
+}
+
+export async function setClipboardTransformed(
+  id: number,
+  transforms: Transform[],
+  save = false
+): Promise<string> {
+  return await invoke<string>("set_clipboard_transformed", { id, transforms, save });
+}
+
+export async function setClipboardMerged(
+  ids: number[],
+  separator?: MergeSeparator,
+  template?: string,
+  save = false
+): Promise<string> {
+  return await invoke<string>("set_clipboard_merged", { ids, separator, template, save });
+}
+
+export async function startPasteQueue(ids: number[], order?: QueueOrder): Promise<QueueStatus> {
+  return await invoke<QueueStatus>("start_paste_queue", { ids, order });
+}
+
+export async function collectPasteQueue(order?: QueueOrder): Promise<QueueStatus> {
+  return await invoke<QueueStatus>("collect_paste_queue", { order });
+}
+
+export async function clearPasteQueue(): Promise<void> {
+  await invoke<void>("clear_paste_queue");
+}
+
+export async function getPasteQueueStatus(): Promise<QueueStatus | null> {
+  try {
+    return await invoke<QueueStatus>("get_paste_queue_status");
+  } catch {
+    return null;
+  }
+}
+
+export async function pasteQueueNext(mode?: PasteMode): Promise<number | null> {
+  return await invoke<number | null>("paste_queue_next", { mode });
+}
+
+export async function copyColorAs(id: number, notation: ColorNotation): Promise<string> {
+  return await invoke<string>("copy_color_as", { id, notation });
+}
+
+export async function copyStructuredAs(id: number, output: StructuredOutput): Promise<string> {
+  return await invoke<string>("copy_structured_as", { id, output });
+}
+
 export async function getCursorPosition(): Promise<{ x: number; y: number }> {
   try {
     const [x, y] = await invoke<[number, number]>("get_cursor_position");
@@ -70,6 +322,35 @@ export async function setHotkey(hotkey: string): Promise<void> {
   }
 }
 
+export async function getSettings(): Promise<Settings> {
+  try {
+    return await invoke<Settings>("get_settings");
+  } catch {
+    return {
+      classificationRules: [],
+      disabledClassifiers: [],
+      imageDuplicatePolicy: "keep",
+      defaultPasteMode: "original",
+      restoreClipboardAfterPaste: false,
+      pasteChord: "ctrlV",
+      pasteProfiles: [],
+      abbreviations: []
+    };
+  }
+}
+
+export async function updateSettings(settings: Settings): Promise<void> {
+  await invoke<void>("update_settings", { settings });
+}
+
+export async function listClassifiers(): Promise<ClassifierInfo[]> {
+  try {
+    return await invoke<ClassifierInfo[]>("list_classifiers");
+  } catch {
+    return [];
+  }
+}
+
 export async function isAutostartEnabled(): Promise<boolean> {
   try {
     return await isEnabled();
@@ -106,6 +387,8 @@ function mockHistory(): ClipboardItem[] {
       format: "text",
       category: "text",
       text: "欢迎使用 Xpaste：这里会显示剪贴板历史。",
+      tags: [],
+      metadata: {},
       createdAt: now - 1000 * 60
     },
     {
@@ -113,6 +396,8 @@ function mockHistory(): ClipboardItem[] {
       format: "text",
       category: "link",
       text: "https://tauri.app",
+      tags: [],
+      metadata: {},
       createdAt: now - 1000 * 120
     },
     {
@@ -121,6 +406,8 @@ function mockHistory(): ClipboardItem[] {
       category: "text",
       color: "#2f80ed",
       text: "#2f80ed",
+      tags: [],
+      metadata: {},
       createdAt: now - 1000 * 180
     }
   ];
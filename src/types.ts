@@ -1,5 +1,49 @@
-﻿export type ClipboardFormat = "text" | "image" | "html" | "file" | "color";
-export type ClipboardCategory = "link" | "image" | "text" | "file";
+﻿import type { ClipboardCategory } from "./bindings/ClipboardCategory";
+import type { ClipboardFormat } from "./bindings/ClipboardFormat";
+import type { EntityKind } from "./bindings/EntityKind";
+import type { PathKind } from "./bindings/PathKind";
+
+// 由 src-tauri 中的 ts-rs 生成，执行 cargo test 时更新
+export type { ClipboardCategory, ClipboardFormat, EntityKind, PathKind };
+
+export type ColorNotation =
+  | "hex"
+  | "rgb"
+  | "hsl"
+  | "hwb"
+  | "oklch"
+  | "name"
+  | "android"
+  | "kotlin";
+
+export type ImageDuplicatePolicy = "keep" | "skip" | "merge";
+
+export type PasteMode = "original" | "plain_text" | "markdown";
+
+export type PasteChord = "ctrlV" | "ctrlShiftV" | "shiftInsert";
+
+export interface ImageRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export type ImageOp =
+  | ({ op: "crop" } & ImageRect)
+  | { op: "resize"; width: number; height: number; keepAspect?: boolean }
+  | { op: "rotate"; degrees: number }
+  | { op: "grayscale" }
+  | { op: "redact"; rects: ImageRect[]; style?: "box" | "blur" };
+
+export type ImageFileFormat = "png" | "jpeg" | "webp" | "bmp";
+
+export interface ImageExportSummary {
+  directory: string;
+  files: string[];
+}
+
+export type StructuredOutput = "pretty" | "minified" | "json" | "yaml";
 
 export type DateRangeType = "today" | "yesterday" | "beforeYesterday" | "custom";
 
@@ -8,6 +52,39 @@ export interface DateRange {
   endTs: number;
 }
 
+export interface LanguageCount {
+  language: string;
+  count: number;
+}
+
+export interface Entity {
+  itemId: number;
+  kind: EntityKind;
+  value: string;
+  normalized: string;
+}
+
+export interface EntityKindCount {
+  kind: EntityKind;
+  count: number;
+}
+
+export interface PathEntry {
+  itemId: number;
+  position: number;
+  path: string;
+  expanded: string;
+  exists: boolean | null;
+  kind: PathKind | null;
+  size: number | null;
+  checkedAt: number | null;
+}
+
+export interface LinkHostCount {
+  host: string;
+  count: number;
+}
+
 export interface ClipboardItem {
   id: number;
   format: ClipboardFormat;
@@ -16,8 +93,126 @@ export interface ClipboardItem {
   html?: string | null;
   filePath?: string | null;
   color?: string | null;
+  linkUrl?: string | null;
+  linkHost?: string | null;
+  linkScheme?: string | null;
+  language?: string | null;
+  dataFormat?: "json" | "yaml" | "xml" | "csv" | null;
+  dataValid?: boolean | null;
+  dataShape?: Record<string, unknown> | null;
+  sourceApp?: string | null;
+  customCategory?: string | null;
+  tags: string[];
+  metadata: Record<string, unknown>;
+  ocrText?: string | null;
   imageBase64?: string | null;
   imageWidth?: number | null;
   imageHeight?: number | null;
   createdAt: number;
+  lastUsedAt?: number | null;
+}
+
+export interface ClassificationRule {
+  name: string;
+  enabled: boolean;
+  regex?: string | null;
+  prefix?: string | null;
+  // 只在 Windows 和 X11 下生效，其他平台取不到来源应用
+  sourceApp?: string | null;
+  minLength?: number | null;
+  maxLength?: number | null;
+  category?: string | null;
+  tags: string[];
+}
+
+export type Transform =
+  | { op: "trim" }
+  | { op: "trimLines" }
+  | { op: "removeEmptyLines" }
+  | { op: "uppercase" }
+  | { op: "lowercase" }
+  | { op: "titleCase" }
+  | { op: "collapseWhitespace" }
+  | { op: "urlEncode" }
+  | { op: "urlDecode" }
+  | { op: "base64Encode" }
+  | { op: "base64Decode" }
+  | { op: "jsonEscape" }
+  | { op: "jsonUnescape" }
+  | { op: "shellEscape" }
+  | { op: "sortLines"; descending?: boolean; caseInsensitive?: boolean }
+  | { op: "reverseLines" }
+  | { op: "dedupeLines" };
+
+export type MergeSeparator = "newline" | "comma" | "tab" | "space" | { custom: string };
+
+export type QueueOrder = "fifo" | "lifo";
+
+export interface QueueStatus {
+  active: boolean;
+  collecting: boolean;
+  order: QueueOrder;
+  itemIds: number[];
+}
+
+export interface Snippet {
+  id: number;
+  name: string;
+  content: string;
+  tags: string[];
+  inputs: string[];
+  createdAt: number;
+  updatedAt: number;
+  lastUsedAt?: number | null;
+}
+
+export interface SnippetInput {
+  name: string;
+  content: string;
+  tags: string[];
+}
+
+export interface SnippetExpansion {
+  text: string;
+  cursorOffset?: number | null;
+}
+
+export interface PasteProfile {
+  name: string;
+  enabled: boolean;
+  apps: string[];
+  pasteChord?: PasteChord | null;
+  pasteMode?: PasteMode | null;
+  transforms: Transform[];
+}
+
+export type AbbreviationTarget =
+  | { kind: "text"; text: string }
+  | { kind: "snippet"; id: number }
+  | { kind: "item"; id: number };
+
+export interface Abbreviation {
+  trigger: string;
+  enabled: boolean;
+  target: AbbreviationTarget;
+}
+
+export interface Settings {
+  classificationRules: ClassificationRule[];
+  disabledClassifiers: string[];
+  ocrLanguage?: string | null;
+  imageDuplicatePolicy: ImageDuplicatePolicy;
+  imageDuplicateThreshold?: number | null;
+  defaultPasteMode: PasteMode;
+  restoreClipboardAfterPaste: boolean;
+  restoreClipboardDelayMs?: number | null;
+  pasteChord: PasteChord;
+  typeDelayMs?: number | null;
+  pasteProfiles: PasteProfile[];
+  abbreviations: Abbreviation[];
+}
+
+export interface ClassifierInfo {
+  id: string;
+  enabled: boolean;
 }